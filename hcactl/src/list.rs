@@ -50,7 +50,7 @@ pub fn run() -> Result<(), color_eyre::Report> {
                     dev.name,
                     dev.slot_name,
                     dev.node_guid,
-                    port.guid.unwrap_or("-".to_string()),
+                    port.guid.map(|g| g.to_string()).unwrap_or("-".to_string()),
                     port.lid,
                     port.subnet.unwrap_or("-".to_string()),
                     port.link_type.to_string(),
@@ -0,0 +1,217 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum CredentialError {
+    #[error("credential '{0}' not found")]
+    NotFound(String),
+    #[error("invalid credential '{0}'")]
+    InvalidCredential(String),
+    #[error("invalid configuration '{0}'")]
+    InvalidConfig(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+/// The source of the secrets, e.g. the passwords of UFM and BMC, which are referenced by name
+/// in the configurations instead of being held inline.
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// The secret of the credential by its name.
+    async fn get(&self, name: &str) -> Result<String, CredentialError>;
+}
+
+/// The configuration of the credential provider to build, e.g. in TOML:
+///
+/// ```toml
+/// [credential_provider]
+/// type = "command"
+/// password_command = "pass show onm/$1"
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum CredentialConfig {
+    /// The environment variable of the name, upper-cased with the other characters than
+    /// letters and digits replaced by '_', e.g. `ufm-admin` with prefix `ONM_` is `ONM_UFM_ADMIN`.
+    Env {
+        #[serde(default)]
+        prefix: String,
+    },
+    /// The file of the name in the directory, which must not be accessible by group and others.
+    File { dir: String },
+    /// The stdout of the command, run by `sh -c` with the name as `$1`.
+    Command { password_command: String },
+    /// The user key of the name in the kernel keyring, read by `keyctl pipe %user:<name>`.
+    Keyring,
+}
+
+impl Default for CredentialConfig {
+    fn default() -> Self {
+        CredentialConfig::Env {
+            prefix: String::new(),
+        }
+    }
+}
+
+/// Parse the provider in the format of `env[:<prefix>]`, `file:<dir>`,
+/// `command:<password_command>` or `keyring`.
+impl FromStr for CredentialConfig {
+    type Err = CredentialError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, arg) = match s.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg.to_string())),
+            None => (s, None),
+        };
+
+        match (kind.trim(), arg) {
+            ("env", prefix) => Ok(CredentialConfig::Env {
+                prefix: prefix.unwrap_or_default(),
+            }),
+            ("file", Some(dir)) if !dir.is_empty() => Ok(CredentialConfig::File { dir }),
+            ("command", Some(cmd)) if !cmd.is_empty() => Ok(CredentialConfig::Command {
+                password_command: cmd,
+            }),
+            ("keyring", None) => Ok(CredentialConfig::Keyring),
+            _ => Err(CredentialError::InvalidConfig(format!(
+                "unknown credential provider <{}>",
+                s
+            ))),
+        }
+    }
+}
+
+pub fn build(conf: CredentialConfig) -> Box<dyn CredentialProvider> {
+    match conf {
+        CredentialConfig::Env { prefix } => Box::new(EnvProvider { prefix }),
+        CredentialConfig::File { dir } => Box::new(FileProvider {
+            dir: PathBuf::from(dir),
+        }),
+        CredentialConfig::Command { password_command } => {
+            Box::new(CommandProvider { password_command })
+        }
+        CredentialConfig::Keyring => Box::new(KeyringProvider {}),
+    }
+}
+
+pub struct EnvProvider {
+    pub prefix: String,
+}
+
+#[async_trait]
+impl CredentialProvider for EnvProvider {
+    async fn get(&self, name: &str) -> Result<String, CredentialError> {
+        let name: String = name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c.to_ascii_uppercase(),
+                false => '_',
+            })
+            .collect();
+        let var = format!("{}{}", self.prefix, name);
+
+        std::env::var(&var).map_err(|_| CredentialError::NotFound(var))
+    }
+}
+
+pub struct FileProvider {
+    pub dir: PathBuf,
+}
+
+#[async_trait]
+impl CredentialProvider for FileProvider {
+    async fn get(&self, name: &str) -> Result<String, CredentialError> {
+        // The name is a file in the directory, not a path to the other files.
+        if name.is_empty() || name.starts_with('.') || name.contains('/') {
+            return Err(CredentialError::InvalidCredential(name.to_string()));
+        }
+
+        let path = self.dir.join(name);
+        let meta = match tokio::fs::metadata(&path).await {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CredentialError::NotFound(name.to_string()))
+            }
+            Err(e) => return Err(read_error(&path, e)),
+        };
+
+        let mode = meta.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(CredentialError::InvalidConfig(format!(
+                "permissions {:04o} of {} are too open, it should be 0600",
+                mode,
+                path.display()
+            )));
+        }
+
+        let secret = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| read_error(&path, e))?;
+
+        Ok(trim_secret(&secret))
+    }
+}
+
+pub struct CommandProvider {
+    pub password_command: String,
+}
+
+#[async_trait]
+impl CredentialProvider for CommandProvider {
+    async fn get(&self, name: &str) -> Result<String, CredentialError> {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(&self.password_command)
+            .arg("sh")
+            .arg(name);
+
+        run(cmd, &self.password_command, name).await
+    }
+}
+
+pub struct KeyringProvider {}
+
+#[async_trait]
+impl CredentialProvider for KeyringProvider {
+    async fn get(&self, name: &str) -> Result<String, CredentialError> {
+        let mut cmd = Command::new("keyctl");
+        cmd.arg("pipe").arg(format!("%user:{}", name));
+
+        run(cmd, "keyctl", name).await
+    }
+}
+
+/// Run the command and take its stdout as the secret; the stderr is not captured, as the
+/// command may prompt for a passphrase.
+async fn run(mut cmd: Command, desc: &str, name: &str) -> Result<String, CredentialError> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .await
+        .map_err(|e| CredentialError::Internal(format!("failed to run <{}>: {}", desc, e)))?;
+    if !output.status.success() {
+        return Err(CredentialError::NotFound(name.to_string()));
+    }
+
+    let secret = String::from_utf8(output.stdout)
+        .map_err(|_| CredentialError::InvalidCredential(name.to_string()))?;
+
+    Ok(trim_secret(&secret))
+}
+
+// Only the trailing newline is trimmed, as the spaces may be part of the secret.
+fn trim_secret(secret: &str) -> String {
+    secret.trim_end_matches(['\n', '\r']).to_string()
+}
+
+fn read_error(path: &Path, e: std::io::Error) -> CredentialError {
+    CredentialError::Internal(format!("failed to read {}: {}", path.display(), e))
+}
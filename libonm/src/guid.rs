@@ -0,0 +1,102 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid guid '{0}'")]
+pub struct InvalidGuid(pub String);
+
+/// The 64-bit GUID of an InfiniBand node or port.
+///
+/// It's parsed from the forms of the backends, e.g. `1070fd0300176625` of UFM,
+/// `0x1070fd0300176625` of OpenSM and `1070:fd03:0017:6625` of the HCA, so the GUIDs
+/// of the same port are equal whatever their form; all the 16 hex digits are required.
+///
+/// It's displayed and serialized as 16 lowercase hex digits without prefix, which is the
+/// form of UFM; the alternate form `{:#}` has the "0x" prefix of OpenSM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Guid(u64);
+
+impl Guid {
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for Guid {
+    fn from(guid: u64) -> Self {
+        Guid(guid)
+    }
+}
+
+impl From<Guid> for u64 {
+    fn from(guid: Guid) -> Self {
+        guid.0
+    }
+}
+
+impl FromStr for Guid {
+    type Err = InvalidGuid;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidGuid(s.to_string());
+
+        // Either 4 groups of 4 hex digits, or 16 hex digits with an optional "0x" prefix.
+        let g = s.trim().to_lowercase();
+        let groups: Vec<&str> = g.split(':').collect();
+        let hex = match groups.len() {
+            1 => g.strip_prefix("0x").unwrap_or(&g).to_string(),
+            4 if groups.iter().all(|group| group.len() == 4) => groups.concat(),
+            _ => return Err(invalid()),
+        };
+        if hex.len() != 16 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        u64::from_str_radix(&hex, 16)
+            .map(Guid)
+            .map_err(|_| invalid())
+    }
+}
+
+impl TryFrom<&str> for Guid {
+    type Error = InvalidGuid;
+
+    fn try_from(guid: &str) -> Result<Self, Self::Error> {
+        guid.parse()
+    }
+}
+
+impl TryFrom<String> for Guid {
+    type Error = InvalidGuid;
+
+    fn try_from(guid: String) -> Result<Self, Self::Error> {
+        guid.parse()
+    }
+}
+
+impl Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Padded, so the GUIDs are aligned in the tables.
+        if f.alternate() {
+            f.pad(&format!("0x{:016x}", self.0))
+        } else {
+            f.pad(&format!("{:016x}", self.0))
+        }
+    }
+}
+
+impl Serialize for Guid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Guid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let guid = String::deserialize(deserializer)?;
+        guid.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -39,6 +39,7 @@ use wrappers::ib::{
     ibv_open_device, ibv_port_attr, ibv_query_device, ibv_query_gid, ibv_query_port,
 };
 
+use crate::guid::Guid;
 use types::{DevicePtr, IbDevice, IbPort, IbPortLinkType, IbPortPhysState, IbPortState, PciDevice};
 use utils::cstr_to_string;
 
@@ -148,17 +149,8 @@ fn list_ib_ports() -> io::Result<HashMap<String, Vec<IbPort>>> {
                             (*gid_ptr).raw[6],
                             (*gid_ptr).raw[7]
                         )),
-                        Some(format!(
-                            "{:02x}{:02x}:{:02x}{:02x}:{:02x}{:02x}:{:02x}{:02x}",
-                            (*gid_ptr).raw[8],
-                            (*gid_ptr).raw[9],
-                            (*gid_ptr).raw[10],
-                            (*gid_ptr).raw[11],
-                            (*gid_ptr).raw[12],
-                            (*gid_ptr).raw[13],
-                            (*gid_ptr).raw[14],
-                            (*gid_ptr).raw[15]
-                        )),
+                        // The port GUID is the interface ID of the GID, in network order.
+                        Some(Guid::from(u64::from_be((*gid_ptr).global.interface_id))),
                     ),
                 };
 
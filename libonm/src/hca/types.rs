@@ -20,8 +20,9 @@ use std::ptr::NonNull;
 
 use libudev::Device;
 
-use super::utils::{get_property, get_sysattr};
+use super::utils::{get_guid, get_property, get_sysattr};
 use super::wrappers::ib::{self, ibv_device, ibv_device_attr};
+use crate::guid::Guid;
 
 #[derive(Clone)]
 pub struct PciDevice {
@@ -54,9 +55,9 @@ impl TryFrom<Device> for PciDevice {
 pub struct IbDevice {
     pub name: String,
     pub slot_name: String,
-    pub node_guid: String,
+    pub node_guid: Guid,
     pub node_desc: String,
-    pub sys_image_guid: String,
+    pub sys_image_guid: Guid,
     pub fw_ver: String,
     pub board_id: String,
     pub ib_ports: Vec<IbPort>,
@@ -72,9 +73,9 @@ impl TryFrom<Device> for IbDevice {
         Ok(Self {
             name: get_property(&dev, "NAME")?.to_string(),
             slot_name,
-            node_guid: get_sysattr(&dev, "node_guid")?.to_string(),
+            node_guid: get_guid(&dev, "node_guid")?,
             node_desc: get_sysattr(&dev, "node_desc")?.to_string(),
-            sys_image_guid: get_sysattr(&dev, "sys_image_guid")?.to_string(),
+            sys_image_guid: get_guid(&dev, "sys_image_guid")?,
             fw_ver: get_sysattr(&dev, "fw_ver")?.to_string(),
             board_id: get_sysattr(&dev, "board_id")?.to_string(),
             ib_ports: vec![],
@@ -171,7 +172,7 @@ impl TryFrom<u8> for IbPortPhysState {
 #[derive(Clone)]
 pub struct IbPort {
     pub port_num: u8,
-    pub guid: Option<String>,
+    pub guid: Option<Guid>,
     pub subnet: Option<String>,
     pub lid: u16,
     pub link_type: IbPortLinkType,
@@ -19,6 +19,8 @@ use std::io;
 
 use libudev::Device;
 
+use crate::guid::Guid;
+
 pub unsafe fn cstr_to_string(s: *const i8) -> String {
     CStr::from_ptr(s)
         .to_str()
@@ -45,3 +47,10 @@ pub fn get_sysattr<'a>(device: &'a Device, name: &'a str) -> io::Result<&'a str>
             .ok_or_else(io::Error::last_os_error),
     }
 }
+
+/// The GUID of the sysattr, e.g. `1070:fd03:0017:6625` of `node_guid`.
+pub fn get_guid(device: &Device, name: &str) -> io::Result<Guid> {
+    get_sysattr(device, name)?
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -1,5 +1,10 @@
+pub mod cred;
+pub mod guid;
 pub mod hca;
 pub mod sm;
 pub mod xpu;
 
 mod rest;
+
+pub use guid::Guid;
+pub use rest::{RestFixture, RestPolicy};
@@ -0,0 +1,174 @@
+use std::sync::Mutex;
+
+use http::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{redact, RestError};
+
+// The records of all the clients are appended to the fixture files one by one.
+static RECORD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Record the requests of a RestClient into a fixture file, or replay them from it without
+/// the server, e.g. for the regression tests of UFM and BMC.
+///
+/// The fixture is a JSON file of the requests with their responses; the credentials are
+/// scrubbed, and the Authorization header and the address of the server are not recorded.
+/// The bodies which are not JSON are replaced by a placeholder, as the secrets in them can
+/// not be located.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestFixture {
+    /// Append the requests and their responses to the file, which is created if not exists.
+    Record(String),
+    /// Reply the requests by the responses in the file instead of sending them; the
+    /// responses of the same method and path are replied in the order they were recorded.
+    Replay(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct FixtureFile {
+    #[serde(default)]
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(super) struct Interaction {
+    method: String,
+    path: String,
+    /// The body of the request, empty if none.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    request: String,
+    status: u16,
+    /// The body of the response.
+    #[serde(default)]
+    body: String,
+}
+
+pub(super) enum Fixture {
+    Record(String),
+    Replay {
+        path: String,
+        // The interactions which are not replied yet.
+        interactions: Mutex<Vec<Option<Interaction>>>,
+    },
+}
+
+impl Fixture {
+    pub(super) fn new(conf: &RestFixture) -> Result<Self, RestError> {
+        match conf {
+            RestFixture::Record(path) => Ok(Fixture::Record(path.clone())),
+            RestFixture::Replay(path) => {
+                let fixture = load(path)?.ok_or(RestError::InvalidConfig(format!(
+                    "fixture {} not found",
+                    path
+                )))?;
+
+                Ok(Fixture::Replay {
+                    path: path.clone(),
+                    interactions: Mutex::new(fixture.interactions.into_iter().map(Some).collect()),
+                })
+            }
+        }
+    }
+
+    /// Append the request and its response to the fixture file in record mode.
+    pub(super) fn record(
+        &self,
+        method: &Method,
+        path: &str,
+        request: &str,
+        status: StatusCode,
+        body: &str,
+    ) -> Result<(), RestError> {
+        let Fixture::Record(file) = self else {
+            return Ok(());
+        };
+
+        let _lock = RECORD_LOCK.lock().unwrap();
+        let mut fixture = load(file)?.unwrap_or_default();
+        fixture.interactions.push(Interaction {
+            method: method.to_string(),
+            path: normalize(path),
+            request: redact(request),
+            status: status.as_u16(),
+            body: redact(body),
+        });
+
+        let data = serde_json::to_string_pretty(&fixture)?;
+        std::fs::write(file, data + "\n")
+            .map_err(|e| RestError::Internal(format!("failed to write {}: {}", file, e)))
+    }
+
+    /// The recorded response of the request in replay mode, None in record mode.
+    pub(super) fn replay(
+        &self,
+        method: &Method,
+        path: &str,
+        request: &str,
+    ) -> Option<Result<(StatusCode, String), RestError>> {
+        let Fixture::Replay {
+            path: file,
+            interactions,
+        } = self
+        else {
+            return None;
+        };
+
+        let path = normalize(path);
+        let mut interactions = interactions.lock().unwrap();
+        let Some(interaction) = interactions
+            .iter_mut()
+            .find(|i| matches!(i, Some(i) if i.method == method.as_str() && i.path == path))
+            .and_then(Option::take)
+        else {
+            return Some(Err(RestError::Internal(format!(
+                "no response of {} {} in fixture {}",
+                method, path, file
+            ))));
+        };
+
+        if !same_body(&interaction.request, &redact(request)) {
+            return Some(Err(RestError::Internal(format!(
+                "the request of {} {} is not the one in fixture {}: {}",
+                method, path, file, interaction.request
+            ))));
+        }
+
+        Some(
+            StatusCode::from_u16(interaction.status)
+                .map(|status| (status, interaction.body))
+                .map_err(|_| {
+                    RestError::InvalidConfig(format!(
+                        "invalid status {} in fixture {}",
+                        interaction.status, file
+                    ))
+                }),
+        )
+    }
+}
+
+fn load(file: &str) -> Result<Option<FixtureFile>, RestError> {
+    match std::fs::read_to_string(file) {
+        Ok(data) => serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| RestError::InvalidConfig(format!("invalid fixture {}: {}", file, e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(RestError::Internal(format!(
+            "failed to read {}: {}",
+            file, e
+        ))),
+    }
+}
+
+fn normalize(path: &str) -> String {
+    format!("/{}", path.trim_matches('/'))
+}
+
+// The bodies are compared as JSON if they are, so the fixtures can be formatted by hand.
+fn same_body(expected: &str, actual: &str) -> bool {
+    let parse = |s: &str| serde_json::from_str::<serde_json::Value>(s).ok();
+    match (parse(expected), parse(actual)) {
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => expected.trim() == actual.trim(),
+    }
+}
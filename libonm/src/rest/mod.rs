@@ -0,0 +1,546 @@
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+use bytes::Bytes;
+use http::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Instrument;
+use url::Url;
+
+use reqwest::{header::HeaderValue, header::ACCEPT, header::AUTHORIZATION, header::CONTENT_TYPE};
+use reqwest::{header::RETRY_AFTER, Certificate, Identity};
+
+mod fixture;
+
+use fixture::Fixture;
+pub use fixture::RestFixture;
+
+// The statuses of the requests which are retried, as the server may recover soon.
+const RETRY_STATUS: [StatusCode; 4] = [
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+// The max delay between two retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// The placeholder of the credentials in the logs.
+const REDACTED: &str = "<redacted>";
+// The JSON fields whose values are redacted in the logs, matched case-insensitively by
+// substring, e.g. `Password`, `new_password` and `access_token`.
+const SECRET_FIELDS: [&str; 4] = ["password", "token", "secret", "authorization"];
+
+pub struct RestClient {
+    // The URL which the paths of the requests are joined onto, without the trailing '/',
+    // e.g. `https://ufm:8443/proxy/ufmRest`.
+    base_url: String,
+    auth: RestAuth,
+    policy: RestPolicy,
+    client: reqwest::Client,
+    fixture: Option<Fixture>,
+}
+
+/// The authentication sent with every request of a RestClient; the password and token are
+/// redacted in its Debug.
+#[derive(Clone)]
+pub enum RestAuth {
+    /// HTTP basic authentication.
+    Basic { username: String, password: String },
+    /// A pre-issued access token, sent as `Authorization: <scheme> <token>`.
+    Token { scheme: String, token: String },
+    /// No Authorization header.
+    None,
+}
+
+impl fmt::Debug for RestAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &REDACTED)
+                .finish(),
+            RestAuth::Token { scheme, .. } => f
+                .debug_struct("Token")
+                .field("scheme", scheme)
+                .field("token", &REDACTED)
+                .finish(),
+            RestAuth::None => write!(f, "None"),
+        }
+    }
+}
+
+/// The PEM files for TLS client authentication.
+#[derive(Clone, Debug)]
+pub struct RestCert {
+    /// The CA bundle to verify the server; the server certificate is not verified if it's None.
+    pub ca_crt: Option<String>,
+    /// The client key in PKCS#8 format.
+    pub tls_key: String,
+    /// The client certificate.
+    pub tls_crt: String,
+}
+
+/// The timeouts, retries and transport of the requests of a RestClient.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestPolicy {
+    /// The timeout to connect to the server, in seconds.
+    pub connect_timeout: u64,
+    /// The timeout of a request, from connecting to reading the whole response, in seconds.
+    pub timeout: u64,
+    /// The max retries of the idempotent requests, e.g. GET, PUT and DELETE; 0 to not retry.
+    pub retries: u32,
+    /// The delay before the first retry in milliseconds, which is doubled for every retry
+    /// with jitter; `Retry-After` of the server is used instead if any.
+    pub backoff: u64,
+    /// Allow the credentials to be sent over plain `http`, e.g. to a server behind a trusted
+    /// tunnel; a RestClient with credentials is refused for an `http` address by default.
+    pub insecure_http: bool,
+}
+
+impl Default for RestPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout: 10,
+            timeout: 60,
+            retries: 3,
+            backoff: 500,
+            insecure_http: false,
+        }
+    }
+}
+
+pub struct RestConfig {
+    /// The URL of the server, e.g. `https://ufm`, `https://jump:8443/ufm` or
+    /// `http://127.0.0.1:8080`; `https` is used if the scheme is missing. The credentials are
+    /// only sent over `http` if RestPolicy::insecure_http is set.
+    pub address: String,
+    /// The prefix appended to the path of the address for every request, e.g. `/ufmRest`.
+    pub base_path: String,
+    pub auth: RestAuth,
+    pub cert: Option<RestCert>,
+    pub policy: RestPolicy,
+    /// Record the requests into a fixture file, or replay them from it.
+    pub fixture: Option<RestFixture>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmptyResponse {}
+
+#[derive(Error, Debug)]
+pub enum RestError {
+    #[error("{0}")]
+    Internal(String),
+    #[error("{0}")]
+    Json(String),
+    #[error("{0}")]
+    Http(String),
+    /// The server replied 404, with the path of the request and the body of the response.
+    #[error("'{path}' not found: {body}")]
+    NotFound { path: String, body: String },
+    /// The server replied 401 or 403.
+    #[error("failed to auth '{path}': {body}")]
+    AuthFailure { path: String, body: String },
+    /// The server replied another status which is not successful, e.g. 400 or 500.
+    #[error("'{path}' failed with status {status}: {body}")]
+    Status {
+        status: u16,
+        path: String,
+        body: String,
+    },
+    #[error("invalid configuration '{0}'")]
+    InvalidConfig(String),
+}
+
+impl RestError {
+    fn from_status(status: StatusCode, path: &str, body: String) -> Self {
+        let path = path.to_string();
+        let body = body.trim().to_string();
+
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                RestError::AuthFailure { path, body }
+            }
+            StatusCode::NOT_FOUND => RestError::NotFound { path, body },
+            _ => RestError::Status {
+                status: status.as_u16(),
+                path,
+                body,
+            },
+        }
+    }
+
+    /// The body of the response if it's not empty, or the path of the request; None if
+    /// the error is not replied by the server.
+    pub fn reason(&self) -> Option<String> {
+        match self {
+            RestError::NotFound { path, body }
+            | RestError::AuthFailure { path, body }
+            | RestError::Status { path, body, .. } => match body.is_empty() {
+                true => Some(path.clone()),
+                false => Some(body.clone()),
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RestError {
+    fn from(value: reqwest::Error) -> Self {
+        tracing::debug!("{:?}", value);
+        RestError::Http(value.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RestError {
+    fn from(value: serde_json::Error) -> Self {
+        tracing::debug!("{:?}", value);
+        RestError::Json(value.to_string())
+    }
+}
+
+impl From<std::io::Error> for RestError {
+    fn from(value: std::io::Error) -> Self {
+        RestError::Http(value.to_string())
+    }
+}
+
+impl RestClient {
+    pub fn new(config: &RestConfig) -> Result<Self, RestError> {
+        let base_url = base_url(&config.address, &config.base_path)?;
+        if base_url.starts_with("http://")
+            && !matches!(config.auth, RestAuth::None)
+            && !config.policy.insecure_http
+        {
+            return Err(RestError::InvalidConfig(format!(
+                "the credentials are sent in plain text to {}, use https or set insecure_http",
+                base_url
+            )));
+        }
+
+        let (ca_crt, identity) = match &config.cert {
+            Some(cert) => {
+                let ca_crt = match &cert.ca_crt {
+                    Some(path) => Certificate::from_pem_bundle(&read_pem(path)?).map_err(|_| {
+                        RestError::InvalidConfig(format!("invalid CA certificate {}", path))
+                    })?,
+                    None => vec![],
+                };
+                let identity =
+                    Identity::from_pkcs8_pem(&read_pem(&cert.tls_crt)?, &read_pem(&cert.tls_key)?)
+                        .map_err(|_| {
+                            RestError::InvalidConfig("invalid client certificate".to_string())
+                        })?;
+
+                (ca_crt, Some(identity))
+            }
+            None => (vec![], None),
+        };
+
+        // The client is kept for all the requests, so the connections are reused.
+        let mut builder = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_secs(config.policy.connect_timeout))
+            .timeout(Duration::from_secs(config.policy.timeout));
+        if ca_crt.is_empty() {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        for ca in ca_crt {
+            builder = builder.add_root_certificate(ca);
+        }
+        if let Some(identity) = identity {
+            builder = builder.identity(identity);
+        }
+
+        Ok(RestClient {
+            base_url,
+            auth: config.auth.clone(),
+            policy: config.policy.clone(),
+            client: builder.build()?,
+            fixture: config.fixture.as_ref().map(Fixture::new).transpose()?,
+        })
+    }
+
+    pub async fn get<'a, T: DeserializeOwned>(&self, path: &str) -> Result<T, RestError> {
+        let resp = self.execute_request(Method::GET, path, None).await?;
+        parse(&resp)
+    }
+
+    pub async fn list<'a, T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, RestError> {
+        let resp = self.execute_request(Method::GET, path, None).await?;
+        parse(&resp)
+    }
+
+    pub async fn put<'a, S: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        o: &S,
+    ) -> Result<T, RestError> {
+        let input = serde_json::to_string(o)
+            .map_err(|_| RestError::InvalidConfig("invalid input".to_string()))?;
+        let resp = self.execute_request(Method::PUT, path, Some(input)).await?;
+
+        parse(&resp)
+    }
+
+    pub async fn post<'a, S: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        o: &S,
+    ) -> Result<T, RestError> {
+        let input = serde_json::to_string(o)
+            .map_err(|_| RestError::InvalidConfig("invalid input".to_string()))?;
+        let resp = self
+            .execute_request(Method::POST, path, Some(input))
+            .await?;
+
+        parse(&resp)
+    }
+
+    pub async fn delete<'a, T: DeserializeOwned>(&self, path: &str) -> Result<T, RestError> {
+        let resp = self.execute_request(Method::DELETE, path, None).await?;
+
+        parse(&resp)
+    }
+
+    pub async fn patch<'a, S: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        o: &S,
+    ) -> Result<T, RestError> {
+        let input = serde_json::to_string(o)
+            .map_err(|_| RestError::InvalidConfig("invalid input".to_string()))?;
+        let resp = self
+            .execute_request(Method::PATCH, path, Some(input))
+            .await?;
+
+        parse(&resp)
+    }
+
+    async fn execute_request(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<String>,
+    ) -> Result<String, RestError> {
+        // The span of the request is only recorded when the debug level of `libonm::rest` is
+        // enabled, e.g. `RUST_LOG=libonm::rest=debug`.
+        let span = tracing::debug_span!("request", %method, path);
+        let start = Instant::now();
+
+        let res = self
+            .send_request(method, path, data)
+            .instrument(span.clone())
+            .await;
+
+        span.in_scope(|| {
+            let latency_ms = start.elapsed().as_millis() as u64;
+            match &res {
+                Ok((status, _)) => tracing::debug!(status, latency_ms, "Request completed"),
+                Err(e) => tracing::debug!(latency_ms, error = %e, "Request failed"),
+            }
+        });
+
+        res.map(|(_, body)| body)
+    }
+
+    async fn send_request(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<String>,
+    ) -> Result<(u16, String), RestError> {
+        let url = format!("{}/{}", self.base_url, path.trim_matches('/'));
+
+        let data = data.unwrap_or_default();
+        tracing::debug!(
+            "Method: {method}, URL: {url}, Auth: <{0:?}>, Body: <{1}>",
+            self.auth,
+            redact(&data)
+        );
+
+        let replay = self
+            .fixture
+            .as_ref()
+            .and_then(|f| f.replay(&method, path, &data));
+        let (status, body) = match replay {
+            Some(resp) => resp?,
+            None => {
+                self.send_with_retry(&method, &url, Bytes::from(data.clone()))
+                    .await?
+            }
+        };
+        if let Some(fixture) = &self.fixture {
+            fixture.record(&method, path, &data, status, &body)?;
+        }
+
+        if !status.is_success() {
+            tracing::debug!("Status: {status}, Body: <{}>", redact(&body));
+            return Err(RestError::from_status(status, path, body));
+        }
+
+        Ok((status.as_u16(), body))
+    }
+
+    async fn send_with_retry(
+        &self,
+        method: &Method,
+        url: &str,
+        body: Bytes,
+    ) -> Result<(StatusCode, String), RestError> {
+        let mut retries = 0;
+        let resp = loop {
+            let resp = self.send(method.clone(), url, body.clone()).await;
+            if retries >= self.policy.retries || !method.is_idempotent() {
+                break resp?;
+            }
+
+            let delay = match &resp {
+                Ok(r) if RETRY_STATUS.contains(&r.status()) => retry_after(r),
+                Ok(_) => break resp?,
+                Err(e) if e.is_connect() || e.is_timeout() => None,
+                Err(_) => break resp?,
+            };
+            let delay = delay
+                .unwrap_or_else(|| self.backoff(retries))
+                .min(MAX_BACKOFF);
+
+            retries += 1;
+            tracing::debug!("Retry {method} {url} in {delay:?}, retries: {retries}");
+            tokio::time::sleep(delay).await;
+        };
+
+        let status = resp.status();
+        Ok((status, resp.text().await?))
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Bytes,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let req = self
+            .client
+            .request(method, url)
+            .header(ACCEPT, HeaderValue::from_static("application/json"))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(body);
+        let req = match &self.auth {
+            RestAuth::Basic { username, password } => req.basic_auth(username, Some(password)),
+            RestAuth::Token { scheme, token } => {
+                req.header(AUTHORIZATION, format!("{} {}", scheme, token))
+            }
+            RestAuth::None => req,
+        };
+
+        req.send().await
+    }
+
+    /// The exponential backoff of the retry with jitter, in [50%, 100%] of the backoff.
+    fn backoff(&self, retries: u32) -> Duration {
+        let backoff = self
+            .policy
+            .backoff
+            .saturating_mul(1 << retries.min(16))
+            .min(MAX_BACKOFF.as_millis() as u64);
+
+        Duration::from_millis(backoff / 2 + fastrand::u64(0..=backoff / 2))
+    }
+}
+
+/// The delay in the `Retry-After` header, in seconds or an HTTP date.
+fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    let v = resp.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    match v.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => httpdate::parse_http_date(v)
+            .ok()?
+            .duration_since(SystemTime::now())
+            .ok(),
+    }
+}
+
+/// The URL of the address joined with the base path, keeping the scheme, port and the path
+/// of the address.
+fn base_url(address: &str, base_path: &str) -> Result<String, RestError> {
+    let address = match address.contains("://") {
+        true => address.to_string(),
+        false => format!("https://{}", address),
+    };
+    let url = Url::parse(&address)
+        .map_err(|_| RestError::InvalidConfig(format!("invalid url '{}'", address)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(RestError::InvalidConfig(format!(
+            "unsupported scheme '{}'",
+            url.scheme()
+        )));
+    }
+    let host = url.host_str().ok_or(RestError::InvalidConfig(format!(
+        "invalid host '{}'",
+        address
+    )))?;
+
+    let mut base_url = format!("{}://{}", url.scheme(), host);
+    if let Some(port) = url.port() {
+        base_url = format!("{}:{}", base_url, port);
+    }
+    for p in [url.path(), base_path] {
+        let p = p.trim_matches('/');
+        if !p.is_empty() {
+            base_url = format!("{}/{}", base_url, p);
+        }
+    }
+
+    Ok(base_url)
+}
+
+/// The body with the values of the secret fields redacted for the logs and the fixtures; the
+/// body is replaced entirely if it's not JSON, as the secrets can not be located.
+fn redact(body: &str) -> String {
+    if body.trim().is_empty() {
+        return String::new();
+    }
+
+    scrub(body).unwrap_or_else(|| format!("{} ({} bytes)", REDACTED, body.len()))
+}
+
+/// The JSON body with the values of the secret fields redacted, None if it's not JSON.
+fn scrub(body: &str) -> Option<String> {
+    fn redact_value(v: &mut serde_json::Value) {
+        match v {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map.iter_mut() {
+                    let k = k.to_lowercase();
+                    match SECRET_FIELDS.iter().any(|f| k.contains(f)) {
+                        true => *v = serde_json::Value::String(REDACTED.to_string()),
+                        false => redact_value(v),
+                    }
+                }
+            }
+            serde_json::Value::Array(vs) => vs.iter_mut().for_each(redact_value),
+            _ => {}
+        }
+    }
+
+    let mut v = serde_json::from_str::<serde_json::Value>(body).ok()?;
+    redact_value(&mut v);
+
+    Some(v.to_string())
+}
+
+fn parse<T: DeserializeOwned>(resp: &str) -> Result<T, RestError> {
+    // UFM replies an empty body to most of the changes.
+    let resp = match resp.trim() {
+        "" => "null",
+        resp => resp,
+    };
+
+    serde_json::from_str(resp).map_err(|_| RestError::InvalidConfig("invalid response".to_string()))
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, RestError> {
+    std::fs::read(path)
+        .map_err(|e| RestError::InvalidConfig(format!("failed to read {}: {}", path, e)))
+}
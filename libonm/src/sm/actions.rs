@@ -0,0 +1,112 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::PhysicalPort;
+use super::UFMError;
+use crate::guid::Guid;
+
+/// A port of the fabric, by its GUID or by the name of its system and its number, e.g.
+/// `1070fd0300176625` or `node1/1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortId {
+    Guid(Guid),
+    Name { system: String, port: u32 },
+}
+
+impl PortId {
+    pub(crate) fn matches(&self, p: &PhysicalPort) -> bool {
+        match self {
+            PortId::Guid(guid) => p.guid == *guid,
+            PortId::Name { system, port } => {
+                p.system_name.eq_ignore_ascii_case(system) && p.number == *port
+            }
+        }
+    }
+}
+
+impl Display for PortId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortId::Guid(guid) => write!(f, "{}", guid),
+            PortId::Name { system, port } => write!(f, "{}/{}", system, port),
+        }
+    }
+}
+
+impl From<Guid> for PortId {
+    fn from(guid: Guid) -> Self {
+        PortId::Guid(guid)
+    }
+}
+
+impl FromStr for PortId {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some((system, port)) = s.rsplit_once('/') else {
+            return Ok(PortId::Guid(s.parse()?));
+        };
+
+        match port.parse::<u32>() {
+            Ok(port) if !system.is_empty() && port > 0 => Ok(PortId::Name {
+                system: system.to_string(),
+                port,
+            }),
+            _ => Err(UFMError::InvalidConfig(format!("invalid port '{}'", s))),
+        }
+    }
+}
+
+/// The administrative action on a port.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PortAction {
+    Enable,
+    Disable,
+    Reset,
+}
+
+impl Display for PortAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortAction::Enable => f.pad("enable"),
+            PortAction::Disable => f.pad("disable"),
+            PortAction::Reset => f.pad("reset"),
+        }
+    }
+}
+
+/// The payload of an action on the port of a system.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ActionRequest {
+    pub action: PortAction,
+    pub object_ids: Vec<String>,
+    pub object_type: String,
+    pub identifier: String,
+    pub params: ActionParams,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ActionParams {
+    /// The name of the port, e.g. `1070fd0300176624_1`.
+    pub port_id: String,
+}
+
+impl ActionRequest {
+    pub fn new(action: PortAction, port: &PhysicalPort) -> Self {
+        Self {
+            action,
+            object_ids: vec![port.system_id.clone()],
+            object_type: "System".to_string(),
+            identifier: "id".to_string(),
+            params: ActionParams {
+                port_id: port.name.clone(),
+            },
+            description: format!("{} port {} by libonm", action, port.name),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::types::PhysicalPort;
+use crate::guid::Guid;
+
+/// The counters of a port from the monitoring of UFM, which are cumulative since the port
+/// was up or the counters were reset.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PortCounters {
+    pub guid: Guid,
+    /// The name of the port in UFM, e.g. `1070fd0300176624_1`.
+    pub name: String,
+    pub system_name: String,
+    /// The active speed of the link of the port, e.g. `HDR`.
+    pub active_speed: String,
+    /// The active width of the link of the port, e.g. `4x`.
+    pub active_width: String,
+    pub symbol_errors: u64,
+    pub link_downed: u64,
+    /// The data transmitted by the port, in 4-octet words as they are counted by the port.
+    pub xmit_data: u64,
+    /// The data received by the port, in 4-octet words as they are counted by the port.
+    pub rcv_data: u64,
+    pub xmit_packets: u64,
+    pub rcv_packets: u64,
+    /// The ticks the port had data to transmit but could not, e.g. for the lack of credits.
+    pub xmit_wait: u64,
+    /// The ticks the port was congested, by the congestion control of the port.
+    pub xmit_time_cong: u64,
+}
+
+impl PortCounters {
+    pub(crate) fn new(port: &PhysicalPort, sample: PortSample) -> Self {
+        Self {
+            guid: port.guid,
+            name: port.name.clone(),
+            system_name: port.system_name.clone(),
+            active_speed: port.active_speed.clone(),
+            active_width: port.active_width.clone(),
+            symbol_errors: sample.symbol_errors,
+            link_downed: sample.link_downed,
+            xmit_data: sample.xmit_data,
+            rcv_data: sample.rcv_data,
+            xmit_packets: sample.xmit_packets,
+            rcv_packets: sample.rcv_packets,
+            xmit_wait: sample.xmit_wait,
+            xmit_time_cong: sample.xmit_time_cong,
+        }
+    }
+}
+
+/// The rates of the counters of a port between two samples, per second; the errors are the
+/// increments between the samples, as they are rare.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PortRates {
+    pub guid: Guid,
+    pub name: String,
+    pub system_name: String,
+    pub symbol_errors: u64,
+    pub link_downed: u64,
+    /// The bytes transmitted per second.
+    pub xmit_bytes: f64,
+    /// The bytes received per second.
+    pub rcv_bytes: f64,
+    pub xmit_packets: f64,
+    pub rcv_packets: f64,
+    pub xmit_wait: f64,
+    pub xmit_time_cong: f64,
+}
+
+impl PortRates {
+    /// The rates between the samples of the same port taken `elapsed` apart; a counter which
+    /// was reset between the samples counts from zero.
+    pub fn between(earlier: &PortCounters, later: &PortCounters, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64();
+        let rate = |earlier: u64, later: u64| {
+            if secs > 0.0 {
+                delta(earlier, later) as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        Self {
+            guid: later.guid,
+            name: later.name.clone(),
+            system_name: later.system_name.clone(),
+            symbol_errors: delta(earlier.symbol_errors, later.symbol_errors),
+            link_downed: delta(earlier.link_downed, later.link_downed),
+            xmit_bytes: rate(earlier.xmit_data, later.xmit_data) * 4.0,
+            rcv_bytes: rate(earlier.rcv_data, later.rcv_data) * 4.0,
+            xmit_packets: rate(earlier.xmit_packets, later.xmit_packets),
+            rcv_packets: rate(earlier.rcv_packets, later.rcv_packets),
+            xmit_wait: rate(earlier.xmit_wait, later.xmit_wait),
+            xmit_time_cong: rate(earlier.xmit_time_cong, later.xmit_time_cong),
+        }
+    }
+}
+
+fn delta(earlier: u64, later: u64) -> u64 {
+    if later >= earlier {
+        later - earlier
+    } else {
+        later
+    }
+}
+
+/// The attributes of the ports which are requested from the monitoring of UFM.
+pub(crate) const PORT_ATTRIBUTES: [&str; 8] = [
+    "Infiniband_SymbolErrorCounter",
+    "Infiniband_LinkDownedCounter",
+    "Infiniband_PortXmitData",
+    "Infiniband_PortRcvData",
+    "Infiniband_PortXmitPkts",
+    "Infiniband_PortRcvPkts",
+    "Infiniband_PortXmitWait",
+    "Infiniband_PortXmitTimeCong",
+];
+
+/// The request of a snapshot of the counters of the ports by their names.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct SnapshotRequest {
+    pub scope_object: String,
+    pub monitor_object: String,
+    pub objects: Vec<String>,
+    pub attributes: Vec<String>,
+}
+
+impl SnapshotRequest {
+    pub fn ports(names: Vec<String>) -> Self {
+        Self {
+            scope_object: "port".to_string(),
+            monitor_object: "port".to_string(),
+            objects: names,
+            attributes: PORT_ATTRIBUTES.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// The snapshot of UFM: the samples by the port names, by the type of the object, by the
+/// timestamp of the sample.
+pub(crate) type Snapshot = BTreeMap<String, BTreeMap<String, BTreeMap<String, PortSample>>>;
+
+/// The counters of a port in a snapshot of UFM.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct PortSample {
+    #[serde(
+        rename = "Infiniband_SymbolErrorCounter",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub symbol_errors: u64,
+    #[serde(
+        rename = "Infiniband_LinkDownedCounter",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub link_downed: u64,
+    #[serde(
+        rename = "Infiniband_PortXmitData",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub xmit_data: u64,
+    #[serde(
+        rename = "Infiniband_PortRcvData",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub rcv_data: u64,
+    #[serde(
+        rename = "Infiniband_PortXmitPkts",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub xmit_packets: u64,
+    #[serde(
+        rename = "Infiniband_PortRcvPkts",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub rcv_packets: u64,
+    #[serde(
+        rename = "Infiniband_PortXmitWait",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub xmit_wait: u64,
+    #[serde(
+        rename = "Infiniband_PortXmitTimeCong",
+        default,
+        deserialize_with = "counter"
+    )]
+    pub xmit_time_cong: u64,
+}
+
+impl From<&PortCounters> for PortSample {
+    fn from(c: &PortCounters) -> Self {
+        Self {
+            symbol_errors: c.symbol_errors,
+            link_downed: c.link_downed,
+            xmit_data: c.xmit_data,
+            rcv_data: c.rcv_data,
+            xmit_packets: c.xmit_packets,
+            rcv_packets: c.rcv_packets,
+            xmit_wait: c.xmit_wait,
+            xmit_time_cong: c.xmit_time_cong,
+        }
+    }
+}
+
+// UFM replies the counters in integers, floats or strings, e.g. 12, 12.0 or "12".
+fn counter<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Counter {
+        Integer(u64),
+        Float(f64),
+        String(String),
+    }
+
+    match Counter::deserialize(deserializer)? {
+        Counter::Integer(n) => Ok(n),
+        Counter::Float(n) if n >= 0.0 => Ok(n as u64),
+        Counter::Float(n) => Err(serde::de::Error::custom(format!("invalid counter {}", n))),
+        Counter::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
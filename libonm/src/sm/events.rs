@@ -0,0 +1,244 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::UFMError;
+use crate::guid::Guid;
+
+/// The severity of an event or alarm of UFM, from the least to the most severe; the unknown
+/// severity is the least.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Minor,
+    Critical,
+    #[serde(other)]
+    Unknown,
+}
+
+impl Severity {
+    fn rank(&self) -> u8 {
+        match self {
+            Severity::Unknown => 0,
+            Severity::Info => 1,
+            Severity::Warning => 2,
+            Severity::Minor => 3,
+            Severity::Critical => 4,
+        }
+    }
+}
+
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Unknown => f.pad("Unknown"),
+            Severity::Info => f.pad("Info"),
+            Severity::Warning => f.pad("Warning"),
+            Severity::Minor => f.pad("Minor"),
+            Severity::Critical => f.pad("Critical"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "minor" => Ok(Severity::Minor),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(UFMError::InvalidConfig(format!("invalid severity '{}'", s))),
+        }
+    }
+}
+
+/// An event of UFM, e.g. a port was down or a pkey was changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Event {
+    /// The ID of the event, which is increasing.
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub severity: Severity,
+    /// The time of the event in UTC, e.g. `2024-05-10 09:18:06`.
+    #[serde(default)]
+    pub timestamp: String,
+    /// The type of the object of the event, e.g. `Port` or `Switch`.
+    #[serde(rename = "type", default)]
+    pub object_type: String,
+    /// The name of the object of the event, e.g. `1070fd0300176624_1` of a port.
+    #[serde(default)]
+    pub object_name: String,
+    #[serde(default)]
+    pub object_path: String,
+}
+
+/// An alarm of UFM, which is raised by events and open until it's cleared.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Alarm {
+    /// The ID of the alarm, which is increasing.
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub severity: Severity,
+    /// The time the alarm was raised in UTC, e.g. `2024-05-10 09:18:06`.
+    #[serde(default)]
+    pub timestamp: String,
+    #[serde(rename = "type", default)]
+    pub object_type: String,
+    #[serde(default)]
+    pub object_name: String,
+    #[serde(default)]
+    pub object_path: String,
+    #[serde(default)]
+    pub reason: String,
+    /// The number of the events which raised the alarm.
+    #[serde(default)]
+    pub counter: u64,
+}
+
+/// The filter of the events and alarms; all of them match the default filter.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// The least severity.
+    pub severity: Option<Severity>,
+    /// The GUID of the object, e.g. of a port or of its system.
+    pub guid: Option<Guid>,
+    /// The time window, inclusive; the entries without a valid timestamp are out of it.
+    pub since: Option<SystemTime>,
+    pub until: Option<SystemTime>,
+}
+
+impl EventFilter {
+    pub(crate) fn matches<R: Record>(&self, r: &R) -> bool {
+        if self.severity.is_some_and(|s| r.severity() < s) {
+            return false;
+        }
+
+        if let Some(guid) = self.guid {
+            let guid = guid.to_string();
+            let (name, path) = r.object();
+            if !name.to_lowercase().contains(&guid) && !path.to_lowercase().contains(&guid) {
+                return false;
+            }
+        }
+
+        if self.since.is_some() || self.until.is_some() {
+            let Some(time) = parse_timestamp(r.timestamp()) else {
+                return false;
+            };
+            if self.since.is_some_and(|since| time < since)
+                || self.until.is_some_and(|until| time > until)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// The entries of UFM which are polled by their IDs, i.e. the events and alarms.
+pub(crate) trait Record {
+    fn id(&self) -> u64;
+    fn severity(&self) -> Severity;
+    fn timestamp(&self) -> &str;
+    /// The name and path of the object.
+    fn object(&self) -> (&str, &str);
+}
+
+impl Record for Event {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    fn object(&self) -> (&str, &str) {
+        (&self.object_name, &self.object_path)
+    }
+}
+
+impl Record for Alarm {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    fn object(&self) -> (&str, &str) {
+        (&self.object_name, &self.object_path)
+    }
+}
+
+/// Parse the timestamp of UFM in UTC, e.g. `2024-05-10 09:18:06` or `2024-05-10T09:18:06.123`.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let s = s.trim();
+    let (date, time) = s.split_once([' ', 'T'])?;
+
+    let mut date = date.splitn(3, '-').map(|n| n.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let time = time.trim_end_matches('Z');
+    let time = time.split_once('.').map_or(time, |(t, _)| t);
+    let mut time = time.splitn(3, ':').map(|n| n.parse::<u64>().ok());
+    let (hour, min, sec) = (time.next()??, time.next()??, time.next()??);
+    if hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    // The days from 1970-01-01 in the proleptic Gregorian calendar; the years out of the
+    // range of SystemTime are rejected rather than overflowed.
+    let y = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era.checked_mul(146097)?.checked_add(doe - 719468)?;
+    let secs = u64::try_from(days)
+        .ok()?
+        .checked_mul(86400)?
+        .checked_add(hour * 3600 + min * 60 + sec)?;
+
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
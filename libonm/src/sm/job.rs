@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{UFMError, Ufm};
+
+// The jobs are polled until they're completed, failed or timed out.
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const JOB_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The reply of a request which is run by a job of UFM in the background.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct JobReply {
+    #[serde(alias = "ID", alias = "id")]
+    pub job_id: String,
+}
+
+/// The status of a job of UFM, or of one of its sub-jobs.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct JobStatus {
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// e.g. `Waiting`, `Running`, `Completed` or `Completed With Errors`.
+    #[serde(rename = "Status")]
+    pub status: String,
+    /// The progress of the job in percent.
+    #[serde(rename = "Progress", default)]
+    pub progress: u32,
+    #[serde(rename = "Description", default)]
+    pub description: String,
+    /// The result of the job, e.g. the reason of its failure.
+    #[serde(rename = "Summary", default)]
+    pub summary: String,
+    /// The operation of the job, e.g. `Provisioning` or `Firmware Upgrade`.
+    #[serde(rename = "Operation", default)]
+    pub operation: String,
+    /// The ID of the parent job of a sub-job.
+    #[serde(rename = "Parent ID", default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+}
+
+impl JobStatus {
+    pub fn is_completed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("completed")
+    }
+
+    pub fn is_failed(&self) -> bool {
+        let status = self.status.to_lowercase();
+        ["error", "fail", "abort", "cancel"]
+            .iter()
+            .any(|s| status.contains(s))
+    }
+
+    /// The job is completed or failed, and its status will not change.
+    pub fn is_done(&self) -> bool {
+        self.is_completed() || self.is_failed()
+    }
+}
+
+/// The handle of a job of UFM, which polls the job until it's done, e.g.
+///
+/// ```no_run
+/// # async fn example(ufm: &libonm::sm::Ufm) -> Result<(), libonm::sm::UFMError> {
+/// use std::time::Duration;
+///
+/// let job = ufm.job("12").with_timeout(Duration::from_secs(600));
+/// job.wait_with(|s| println!("{}: {}%", s.status, s.progress)).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Job<'a> {
+    ufm: &'a Ufm,
+    id: String,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl<'a> Job<'a> {
+    pub(crate) fn new(ufm: &'a Ufm, id: &str) -> Self {
+        Self {
+            ufm,
+            id: id.to_string(),
+            interval: JOB_POLL_INTERVAL,
+            timeout: JOB_TIMEOUT,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The time to wait for the job, 5 minutes by default.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// The interval of the polls of the job, 1 second by default.
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    pub async fn status(&self) -> Result<JobStatus, UFMError> {
+        let path = format!("/jobs/{}", self.id);
+        Ok(self.ufm.client.get(&path).await?)
+    }
+
+    /// The sub-jobs of the job, e.g. one per switch of a firmware upgrade.
+    pub async fn sub_jobs(&self) -> Result<Vec<JobStatus>, UFMError> {
+        let path = format!("/jobs?parent_id={}", self.id);
+        Ok(self.ufm.client.list(&path).await?)
+    }
+
+    /// Wait until the job is completed, and return its last status.
+    pub async fn wait(&self) -> Result<JobStatus, UFMError> {
+        self.wait_with(|_| {}).await
+    }
+
+    /// Wait until the job is completed, and report every status of it to `progress`; the job
+    /// is failed if it's not completed in the timeout.
+    pub async fn wait_with(
+        &self,
+        mut progress: impl FnMut(&JobStatus),
+    ) -> Result<JobStatus, UFMError> {
+        let start = Instant::now();
+        loop {
+            let job = self.status().await?;
+            progress(&job);
+
+            if job.is_completed() {
+                return Ok(job);
+            }
+            if job.is_failed() {
+                return Err(UFMError::JobFailed {
+                    reason: self.reason(&job).await,
+                    id: job.id,
+                });
+            }
+            if start.elapsed() >= self.timeout {
+                return Err(UFMError::JobTimeout {
+                    id: job.id,
+                    progress: job.progress,
+                });
+            }
+
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    // The reason of the failed job: its summary, or the summaries of its failed sub-jobs.
+    async fn reason(&self, job: &JobStatus) -> String {
+        if !job.summary.is_empty() {
+            return job.summary.clone();
+        }
+
+        let failed: Vec<String> = self
+            .sub_jobs()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|s| s.is_failed())
+            .map(|s| {
+                if s.summary.is_empty() {
+                    format!("sub-job {} is {}", s.id, s.status.to_lowercase())
+                } else {
+                    format!("sub-job {}: {}", s.id, s.summary)
+                }
+            })
+            .collect();
+
+        if failed.is_empty() {
+            job.status.to_lowercase()
+        } else {
+            failed.join("; ")
+        }
+    }
+}
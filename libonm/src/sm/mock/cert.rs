@@ -0,0 +1,98 @@
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::{X509Builder, X509Name, X509NameBuilder, X509NameRef, X509};
+
+/// The certificates of the mock, which are generated when it starts, so no private key is
+/// kept in the source tree.
+pub(super) struct MockCerts {
+    /// The self-signed certificate of localhost and 127.0.0.1, as the client only speaks
+    /// HTTPS; it's also the CA of the client certificate.
+    pub crt: X509,
+    pub key: PKey<Private>,
+    /// The client certificate which is accepted under the REST prefix of client
+    /// certificates, with its key in PEM.
+    pub client_crt: Vec<u8>,
+    pub client_key: Vec<u8>,
+}
+
+impl MockCerts {
+    pub fn generate() -> Result<Self, ErrorStack> {
+        let key = new_key()?;
+        let name = common_name("localhost")?;
+        let mut builder = new_builder(&name, &name, &key)?;
+        builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+        let san = SubjectAlternativeName::new()
+            .ip("127.0.0.1")
+            .dns("localhost")
+            .build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(san)?;
+        let ski = SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(ski)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+        let crt = builder.build();
+
+        let client_key = new_key()?;
+        let mut builder =
+            new_builder(&common_name("onm-client")?, crt.subject_name(), &client_key)?;
+        builder.append_extension(BasicConstraints::new().critical().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().digital_signature().build()?)?;
+        builder.append_extension(ExtendedKeyUsage::new().client_auth().build()?)?;
+        let aki = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(Some(&crt), None))?;
+        builder.append_extension(aki)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+
+        Ok(MockCerts {
+            client_crt: builder.build().to_pem()?,
+            client_key: client_key.private_key_to_pem_pkcs8()?,
+            crt,
+            key,
+        })
+    }
+}
+
+fn new_key() -> Result<PKey<Private>, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    PKey::from_ec_key(EcKey::generate(&group)?)
+}
+
+fn common_name(cn: &str) -> Result<X509Name, ErrorStack> {
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_nid(Nid::COMMONNAME, cn)?;
+    Ok(name.build())
+}
+
+// The builder of a certificate which is valid for a day, as the mock lives in a test.
+fn new_builder(
+    subject: &X509Name,
+    issuer: &X509NameRef,
+    key: &PKey<Private>,
+) -> Result<X509Builder, ErrorStack> {
+    let mut serial = BigNum::new()?;
+    serial.rand(128, MsbOption::MAYBE_ZERO, false)?;
+
+    let serial = serial.to_asn1_integer()?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    let not_after = Asn1Time::days_from_now(1)?;
+
+    let mut builder = X509Builder::new()?;
+    builder.set_version(2)?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(subject)?;
+    builder.set_issuer_name(issuer)?;
+    builder.set_pubkey(key)?;
+    builder.set_not_before(&not_before)?;
+    builder.set_not_after(&not_after)?;
+
+    Ok(builder)
+}
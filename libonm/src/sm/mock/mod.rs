@@ -0,0 +1,782 @@
+//! An in-process UFM REST server with in-memory state, to test `Ufm` and `smctl` without a
+//! live UFM, e.g.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), libonm::sm::UFMError> {
+//! use libonm::sm::{self, mock};
+//!
+//! let mock = mock::MockUfm::start().await?;
+//! let ufm = sm::connect(mock.config())?;
+//! assert_eq!(ufm.version().await?, mock::MOCK_VERSION);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use http::{Method, StatusCode};
+use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::guid::Guid;
+use crate::rest::RestPolicy;
+
+use self::cert::MockCerts;
+use super::actions::{ActionRequest, PortAction};
+use super::counters::{PortSample, SnapshotRequest};
+use super::job::JobStatus;
+use super::types::{Configuration, Link, PhysicalPort, System, VirtualPort};
+use super::{
+    Alarm, Event, NewPkey, PKeyQoS, PartitionKey, PartitionQoS, Pkey, PkeyGuids, PortConfig,
+    PortCounters, UFMCert, UFMConfig, UFMError, UFM_BASIC_AUTH_PATH, UFM_CERT_AUTH_PATH,
+    UFM_TOKEN_AUTH_PATH, UFM_TOKEN_SCHEME,
+};
+
+mod cert;
+
+pub const MOCK_USERNAME: &str = "admin";
+pub const MOCK_PASSWORD: &str = "123456";
+pub const MOCK_TOKEN: &str = "mocktoken";
+pub const MOCK_VERSION: &str = "6.11.1-2";
+
+const DEFAULT_PKEY: i32 = 0x7fff;
+
+/// The partition kept by the mock, as UFM reports it with `qos_conf` and `guids_data`.
+#[derive(Debug, Clone)]
+pub struct MockPartition {
+    pub name: String,
+    pub ipoib: bool,
+    pub qos: PartitionQoS,
+    pub guids: Vec<PortConfig>,
+}
+
+struct InjectedError {
+    method: Method,
+    path: String,
+    status: StatusCode,
+}
+
+struct MockJob {
+    status: JobStatus,
+    /// The polls of the job until it's completed, and the polls left.
+    polls: u32,
+    left: u32,
+}
+
+struct State {
+    partitions: BTreeMap<i32, MockPartition>,
+    ports: Vec<PhysicalPort>,
+    vports: Vec<VirtualPort>,
+    systems: Vec<System>,
+    links: Vec<Link>,
+    counters: HashMap<Guid, PortCounters>,
+    events: Vec<Event>,
+    alarms: Vec<Alarm>,
+    jobs: BTreeMap<String, MockJob>,
+    /// The polls of the jobs until they're completed; the changes of the partitions are done
+    /// without jobs if None.
+    job_polls: Option<u32>,
+    configuration: Configuration,
+    errors: Vec<InjectedError>,
+    requests: Vec<String>,
+}
+
+/// The mock UFM, which serves until it's dropped.
+pub struct MockUfm {
+    addr: SocketAddr,
+    certs: MockCerts,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+impl MockUfm {
+    /// Start the mock on a random port of localhost, with the default partition only.
+    pub async fn start() -> Result<Self, UFMError> {
+        let certs = MockCerts::generate().map_err(|e| UFMError::Unknown(e.to_string()))?;
+        let acceptor = Arc::new(acceptor(&certs).map_err(|e| UFMError::Unknown(e.to_string()))?);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| UFMError::Unknown(e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| UFMError::Unknown(e.to_string()))?;
+
+        let mut partitions = BTreeMap::new();
+        partitions.insert(
+            DEFAULT_PKEY,
+            MockPartition {
+                name: "Default".to_string(),
+                ipoib: true,
+                qos: PartitionQoS::default(),
+                guids: vec![],
+            },
+        );
+        let state = Arc::new(Mutex::new(State {
+            partitions,
+            ports: vec![],
+            vports: vec![],
+            systems: vec![],
+            links: vec![],
+            counters: HashMap::new(),
+            events: vec![],
+            alarms: vec![],
+            jobs: BTreeMap::new(),
+            job_polls: None,
+            configuration: Configuration {
+                subnet_prefix: "0xfe80000000000000".to_string(),
+                m_key: "0x0000000000000000".to_string(),
+                m_key_per_port: false,
+                sm_key: "0x0000000000000001".to_string(),
+                sa_key: "0x0000000000000001".to_string(),
+                log_file: "/opt/ufm/files/log/opensm.log".to_string(),
+                qos: 0,
+            },
+            errors: vec![],
+            requests: vec![],
+        }));
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(stream) = stream.into_std() else {
+                    continue;
+                };
+                let acceptor = acceptor.clone();
+                let state = server_state.clone();
+                // The connections are served in threads, as they are closed by the clients.
+                std::thread::spawn(move || serve(&acceptor, stream, state));
+            }
+        });
+
+        Ok(MockUfm {
+            addr,
+            certs,
+            state,
+            server,
+        })
+    }
+
+    /// The address of the mock, e.g. `https://127.0.0.1:34567`.
+    pub fn address(&self) -> String {
+        format!("https://{}", self.addr)
+    }
+
+    /// The configuration to connect to the mock by basic authentication.
+    pub fn config(&self) -> UFMConfig {
+        UFMConfig {
+            address: self.address(),
+            username: Some(MOCK_USERNAME.to_string()),
+            password: Some(MOCK_PASSWORD.to_string()),
+            token: None,
+            cert: None,
+            credential: None,
+            policy: RestPolicy::default(),
+            fixture: None,
+            wait: None,
+        }
+    }
+
+    /// The configuration to connect to the mock by the client certificate, whose PEM files
+    /// are written to a temporary directory of the mock.
+    pub fn cert_config(&self) -> Result<UFMConfig, UFMError> {
+        let dir = std::env::temp_dir().join(format!("onm-mock-{}", self.addr.port()));
+        let ca_crt = self
+            .certs
+            .crt
+            .to_pem()
+            .map_err(|e| UFMError::Unknown(e.to_string()))?;
+        let write = |name: &str, pem: &[u8]| {
+            let path = dir.join(name);
+            fs::write(&path, pem).map_err(|e| UFMError::Unknown(e.to_string()))?;
+            Ok::<_, UFMError>(path.to_string_lossy().to_string())
+        };
+
+        fs::create_dir_all(&dir).map_err(|e| UFMError::Unknown(e.to_string()))?;
+        Ok(UFMConfig {
+            username: None,
+            password: None,
+            cert: Some(UFMCert {
+                ca_crt: write("ca.crt", &ca_crt)?,
+                tls_key: write("client.key", &self.certs.client_key)?,
+                tls_crt: write("client.crt", &self.certs.client_crt)?,
+            }),
+            ..self.config()
+        })
+    }
+
+    /// Add a physical port of a computer to the fabric.
+    pub fn add_port(&self, port: PhysicalPort) {
+        self.state.lock().unwrap().ports.push(port);
+    }
+
+    /// Add the first port of a host to the fabric, which is active at HDR 4x; the GUID of the
+    /// host is the GUID of the port minus one, as it is for ConnectX.
+    pub fn add_port_with(&self, guid: Guid, system: &str) {
+        let mut state = self.state.lock().unwrap();
+        let lid = state.ports.len() as i32 + 1;
+        state.ports.push(PhysicalPort {
+            guid,
+            name: format!("{}_1", system),
+            system_id: Guid::from(guid.as_u64().wrapping_sub(1)).to_string(),
+            lid,
+            system_name: system.to_string(),
+            logical_state: "Active".to_string(),
+            number: 1,
+            active_speed: "HDR".to_string(),
+            active_width: "4x".to_string(),
+        });
+    }
+
+    /// Add a virtual port (SR-IOV VF) to the fabric.
+    pub fn add_vport(&self, vport: VirtualPort) {
+        self.state.lock().unwrap().vports.push(vport);
+    }
+
+    /// Add a system, e.g. a switch or a host, to the fabric.
+    pub fn add_system(&self, system: System) {
+        self.state.lock().unwrap().systems.push(system);
+    }
+
+    /// Add a link between the ports of two systems to the fabric.
+    pub fn add_link(&self, link: Link) {
+        self.state.lock().unwrap().links.push(link);
+    }
+
+    /// Set the counters of a port which is added by `add_port`, by its GUID.
+    pub fn set_port_counters(&self, counters: PortCounters) {
+        self.state
+            .lock()
+            .unwrap()
+            .counters
+            .insert(counters.guid, counters);
+    }
+
+    /// Add an event, which is replied in the order of the IDs.
+    pub fn add_event(&self, event: Event) {
+        let mut state = self.state.lock().unwrap();
+        state.events.push(event);
+        state.events.sort_by_key(|e| e.id);
+    }
+
+    /// Raise an alarm, which is replied in the order of the IDs.
+    pub fn add_alarm(&self, alarm: Alarm) {
+        let mut state = self.state.lock().unwrap();
+        state.alarms.push(alarm);
+        state.alarms.sort_by_key(|a| a.id);
+    }
+
+    /// Add a job, or a sub-job with the ID of its parent, which keeps its status as it's
+    /// added, e.g. to fail a job or to never complete it.
+    pub fn add_job(&self, job: JobStatus) {
+        let job = MockJob {
+            status: job,
+            polls: 0,
+            left: 0,
+        };
+        self.state
+            .lock()
+            .unwrap()
+            .jobs
+            .insert(job.status.id.clone(), job);
+    }
+
+    /// Run the changes of the partitions in jobs as UFM does on large fabrics, and complete
+    /// the jobs of the changes and of the port actions after the polls of them; the changes
+    /// themselves are done at once.
+    pub fn run_jobs(&self, polls: u32) {
+        self.state.lock().unwrap().job_polls = Some(polls);
+    }
+
+    /// The partition as it's kept by the mock, None if it does not exist.
+    pub fn partition(&self, pkey: PartitionKey) -> Option<MockPartition> {
+        self.state
+            .lock()
+            .unwrap()
+            .partitions
+            .get(&i32::from(pkey))
+            .cloned()
+    }
+
+    /// Fail the next request of the method to the path with the status, e.g. 401, 404 or 500;
+    /// the path is without the REST prefix and the query, e.g. `/resources/pkeys`.
+    pub fn inject_error(&self, method: Method, path: &str, status: u16) {
+        self.state.lock().unwrap().errors.push(InjectedError {
+            method,
+            path: path.to_string(),
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        });
+    }
+
+    /// The requests received by the mock, e.g. `POST /resources/pkeys/add`.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for MockUfm {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+struct Request {
+    method: Method,
+    path: String,
+    query: HashMap<String, String>,
+    authorization: Option<String>,
+    /// The connection is authenticated by a verified client certificate.
+    client_cert: bool,
+    body: String,
+}
+
+// The acceptor of the mock, which requests a client certificate and verifies it by the
+// certificate of the mock if it's sent.
+fn acceptor(certs: &MockCerts) -> Result<SslAcceptor, openssl::error::ErrorStack> {
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+    builder.set_private_key(&certs.key)?;
+    builder.set_certificate(&certs.crt)?;
+    builder.check_private_key()?;
+    builder.cert_store_mut().add_cert(certs.crt.clone())?;
+    builder.set_verify(SslVerifyMode::PEER);
+
+    Ok(builder.build())
+}
+
+/// Serve the HTTP/1.1 requests of the connection until it's closed by the client.
+fn serve(acceptor: &SslAcceptor, stream: TcpStream, state: Arc<Mutex<State>>) {
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let Ok(stream) = acceptor.accept(stream) else {
+        return;
+    };
+    let client_cert = stream.ssl().peer_certificate().is_some();
+
+    let mut stream = BufReader::new(stream);
+    while let Some(mut req) = read_request(&mut stream) {
+        req.client_cert = client_cert;
+        let (status, body) = handle(&mut state.lock().unwrap(), &req);
+        let resp = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or_default(),
+            body.len(),
+            body
+        );
+        if stream.get_mut().write_all(resp.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request<S: Read>(stream: &mut BufReader<S>) -> Option<Request> {
+    let mut line = String::new();
+    stream.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = Method::from_bytes(parts.next()?.as_bytes()).ok()?;
+    let target = parts.next()?.to_string();
+
+    let mut length = 0;
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        stream.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            match k.trim().to_lowercase().as_str() {
+                "content-length" => length = v.trim().parse().ok()?,
+                "authorization" => authorization = Some(v.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).ok()?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query),
+        None => (target.clone(), ""),
+    };
+    let query = query
+        .split('&')
+        .filter(|q| !q.is_empty())
+        .map(|q| match q.split_once('=') {
+            Some((k, v)) => (k.to_string(), v.to_string()),
+            None => (q.to_string(), String::new()),
+        })
+        .collect();
+
+    Some(Request {
+        method,
+        path,
+        query,
+        authorization,
+        client_cert: false,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+type Response = (StatusCode, String);
+
+fn error(status: StatusCode, msg: impl Into<String>) -> Response {
+    (status, json!({ "error": msg.into() }).to_string())
+}
+
+fn ok(v: Value) -> Response {
+    (StatusCode::OK, v.to_string())
+}
+
+// UFM replies an empty body to the changes.
+fn done() -> Response {
+    (StatusCode::OK, String::new())
+}
+
+// UFM replies the job of the change on large fabrics instead.
+fn changed(state: &mut State, description: &str) -> Response {
+    match state.job_polls {
+        Some(_) => accepted(start_job(state, description)),
+        None => done(),
+    }
+}
+
+fn accepted(job_id: String) -> Response {
+    (
+        StatusCode::ACCEPTED,
+        json!({ "job_id": job_id }).to_string(),
+    )
+}
+
+fn start_job(state: &mut State, description: &str) -> String {
+    let id = (state.jobs.len() + 1..)
+        .map(|n| n.to_string())
+        .find(|id| !state.jobs.contains_key(id))
+        .unwrap_or_default();
+    let polls = state.job_polls.unwrap_or_default();
+    let status = JobStatus {
+        id: id.clone(),
+        status: if polls > 0 { "Running" } else { "Completed" }.to_string(),
+        progress: if polls > 0 { 0 } else { 100 },
+        description: description.to_string(),
+        ..Default::default()
+    };
+    state.jobs.insert(
+        id.clone(),
+        MockJob {
+            status,
+            polls,
+            left: polls,
+        },
+    );
+
+    id
+}
+
+// Poll the job, which is completed after its polls.
+fn poll_job(job: &mut MockJob) -> JobStatus {
+    if job.left > 0 {
+        job.left -= 1;
+        job.status.progress = 100 - 100 * job.left / job.polls;
+        if job.left == 0 {
+            job.status.status = "Completed".to_string();
+        }
+    }
+
+    job.status.clone()
+}
+
+fn payload<T: DeserializeOwned>(req: &Request) -> Result<T, Response> {
+    serde_json::from_str(&req.body)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, format!("invalid payload: {}", e)))
+}
+
+fn pkey_of(s: &str) -> Result<i32, Response> {
+    PartitionKey::try_from(s)
+        .map(i32::from)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+fn authorized(req: &Request, path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(UFM_CERT_AUTH_PATH) {
+        return req.client_cert.then(|| rest.to_string());
+    }
+
+    let basic = base64::engine::general_purpose::STANDARD
+        .encode(format!("{}:{}", MOCK_USERNAME, MOCK_PASSWORD));
+
+    let (expected, rest) = match path.strip_prefix(UFM_TOKEN_AUTH_PATH) {
+        Some(rest) => (format!("{} {}", UFM_TOKEN_SCHEME, MOCK_TOKEN), rest),
+        None => (
+            format!("Basic {}", basic),
+            path.strip_prefix(UFM_BASIC_AUTH_PATH)?,
+        ),
+    };
+
+    match &req.authorization {
+        Some(auth) if *auth == expected => Some(rest.to_string()),
+        _ => None,
+    }
+}
+
+fn handle(state: &mut State, req: &Request) -> Response {
+    let Some(path) = authorized(req, &req.path) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+    let path = format!("/{}", path.trim_matches('/'));
+    state.requests.push(format!("{} {}", req.method, path));
+
+    if let Some(i) = state
+        .errors
+        .iter()
+        .position(|e| e.method == req.method && e.path == path)
+    {
+        let e = state.errors.remove(i);
+        return error(e.status, "injected error");
+    }
+
+    match route(state, req, &path) {
+        Ok(resp) | Err(resp) => resp,
+    }
+}
+
+fn route(state: &mut State, req: &Request, path: &str) -> Result<Response, Response> {
+    let flag = |k: &str| req.query.get(k).map(|v| v == "true").unwrap_or(false);
+    let segs: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+    match (&req.method, segs.as_slice()) {
+        (&Method::GET, ["resources", "pkeys"]) => {
+            if !flag("qos_conf") && !flag("guids_data") {
+                let pkeys: Vec<String> = state.partitions.keys().map(|k| pkey_str(*k)).collect();
+                return Ok(ok(json!(pkeys)));
+            }
+
+            let pkeys: serde_json::Map<String, Value> = state
+                .partitions
+                .iter()
+                .map(|(k, p)| {
+                    (
+                        pkey_str(*k),
+                        partition_json(p, flag("qos_conf"), flag("guids_data")),
+                    )
+                })
+                .collect();
+            Ok(ok(Value::Object(pkeys)))
+        }
+        (&Method::GET, ["resources", "pkeys", pkey]) => {
+            let p = state
+                .partitions
+                .get(&pkey_of(pkey)?)
+                .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("pkey {} not found", pkey)))?;
+            Ok(ok(partition_json(p, flag("qos_conf"), flag("guids_data"))))
+        }
+        (&Method::POST, ["resources", "pkeys", "add"]) => {
+            let p: NewPkey = payload(req)?;
+            let pkey = pkey_of(&p.pkey)?;
+            if state.partitions.contains_key(&pkey) {
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    format!("pkey {} already exists", p.pkey),
+                ));
+            }
+
+            state.partitions.insert(
+                pkey,
+                MockPartition {
+                    name: p.partition,
+                    ipoib: p.ip_over_ib,
+                    qos: PartitionQoS {
+                        mtu_limit: p.mtu_limit,
+                        service_level: p.service_level,
+                        rate_limit: p.rate_limit,
+                    },
+                    guids: vec![],
+                },
+            );
+            Ok(changed(state, "add pkey"))
+        }
+        (&Method::POST, ["resources", "pkeys"]) => {
+            let p: Pkey = payload(req)?;
+            let pkey = pkey_of(&p.pkey)?;
+            let part = state
+                .partitions
+                .entry(pkey)
+                .or_insert_with(|| MockPartition {
+                    name: format!("api_pkey_{}", pkey_str(pkey)),
+                    ipoib: p.ip_over_ib,
+                    qos: PartitionQoS::default(),
+                    guids: vec![],
+                });
+
+            part.ipoib = p.ip_over_ib;
+            for guid in p.guids {
+                part.guids.retain(|g| g.guid != guid);
+                part.guids.push(PortConfig {
+                    guid,
+                    index0: p.index0,
+                    membership: p.membership.clone(),
+                });
+            }
+            Ok(changed(state, "add guids to pkey"))
+        }
+        (&Method::PUT, ["resources", "pkeys", "qos_conf"]) => {
+            let qos: PKeyQoS = payload(req)?;
+            let part = state
+                .partitions
+                .get_mut(&pkey_of(&qos.pkey)?)
+                .ok_or_else(|| {
+                    error(
+                        StatusCode::NOT_FOUND,
+                        format!("pkey {} not found", qos.pkey),
+                    )
+                })?;
+
+            part.qos = PartitionQoS {
+                mtu_limit: qos.mtu_limit,
+                service_level: qos.service_level,
+                rate_limit: qos.rate_limit,
+            };
+            Ok(changed(state, "update qos of pkey"))
+        }
+        (&Method::DELETE, ["resources", "pkeys", pkey]) => {
+            let pkey = pkey_of(pkey)?;
+            if pkey == DEFAULT_PKEY {
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    "the default pkey can not be deleted",
+                ));
+            }
+
+            state.partitions.remove(&pkey).ok_or_else(|| {
+                error(
+                    StatusCode::NOT_FOUND,
+                    format!("pkey {} not found", pkey_str(pkey)),
+                )
+            })?;
+            Ok(changed(state, "delete pkey"))
+        }
+        (&Method::POST, ["actions", "remove_guids_from_pkey"]) => {
+            let p: PkeyGuids = payload(req)?;
+            let part = state
+                .partitions
+                .get_mut(&pkey_of(&p.pkey)?)
+                .ok_or_else(|| {
+                    error(StatusCode::NOT_FOUND, format!("pkey {} not found", p.pkey))
+                })?;
+
+            part.guids.retain(|g| !p.guids.contains(&g.guid));
+            Ok(changed(state, "remove guids from pkey"))
+        }
+        (&Method::GET, ["resources", "ports"]) => {
+            // All the ports of the mock belong to computers.
+            let ports: &[PhysicalPort] = match req.query.get("sys_type") {
+                Some(t) if t != "Computer" => &[],
+                _ => &state.ports,
+            };
+            Ok(ok(json!(ports)))
+        }
+        (&Method::GET, ["resources", "vports"]) => Ok(ok(json!(state.vports))),
+        (&Method::GET, ["resources", "systems"]) => {
+            let systems: Vec<&System> = state
+                .systems
+                .iter()
+                .filter(|s| match req.query.get("type") {
+                    Some(t) => s.system_type.to_string() == *t,
+                    None => true,
+                })
+                .collect();
+            Ok(ok(json!(systems)))
+        }
+        (&Method::GET, ["resources", "links"]) => Ok(ok(json!(state.links))),
+        (&Method::POST, ["monitoring", "snapshot"]) => {
+            let req: SnapshotRequest = payload(req)?;
+            let mut samples = BTreeMap::new();
+            for name in req.objects {
+                let port = state.ports.iter().find(|p| p.name == name);
+                if let Some(c) = port.and_then(|p| state.counters.get(&p.guid)) {
+                    samples.insert(name, PortSample::from(c));
+                }
+            }
+
+            // The samples of the ports at a fixed timestamp, as the mock has no clock.
+            Ok(ok(json!({ "1700000000": { "Port": samples } })))
+        }
+        (&Method::POST, ["actions"]) => {
+            let action: ActionRequest = payload(req)?;
+            let port = state
+                .ports
+                .iter_mut()
+                .find(|p| p.name == action.params.port_id)
+                .ok_or_else(|| {
+                    error(
+                        StatusCode::NOT_FOUND,
+                        format!("port {} not found", action.params.port_id),
+                    )
+                })?;
+
+            // The actions are done at once, and their jobs are completed after their polls.
+            port.logical_state = match action.action {
+                PortAction::Disable => "Disabled".to_string(),
+                PortAction::Enable | PortAction::Reset => "Active".to_string(),
+            };
+            Ok(accepted(start_job(state, &action.description)))
+        }
+        (&Method::GET, ["jobs"]) => {
+            let parent = req.query.get("parent_id");
+            let jobs: Vec<&JobStatus> = state
+                .jobs
+                .values()
+                .map(|j| &j.status)
+                .filter(|j| parent.is_none() || j.parent_id.as_ref() == parent)
+                .collect();
+            Ok(ok(json!(jobs)))
+        }
+        (&Method::GET, ["jobs", id]) => match state.jobs.get_mut(*id) {
+            Some(job) => Ok(ok(json!(poll_job(job)))),
+            None => Err(error(
+                StatusCode::NOT_FOUND,
+                format!("job {} not found", id),
+            )),
+        },
+        (&Method::GET, ["app", "events"]) => Ok(ok(json!(state.events))),
+        (&Method::GET, ["app", "alarms"]) => Ok(ok(json!(state.alarms))),
+        (&Method::GET, ["app", "smconf"]) => Ok(ok(json!(state.configuration))),
+        (&Method::GET, ["app", "ufm_version"]) => {
+            Ok(ok(json!({ "ufm_release_version": MOCK_VERSION })))
+        }
+        _ => Err(error(
+            StatusCode::NOT_FOUND,
+            format!("{} {} not found", req.method, path),
+        )),
+    }
+}
+
+fn pkey_str(pkey: i32) -> String {
+    format!("0x{:x}", pkey)
+}
+
+fn partition_json(p: &MockPartition, qos_conf: bool, guids_data: bool) -> Value {
+    let mut v = json!({
+        "partition": p.name,
+        "ip_over_ib": p.ipoib,
+    });
+    if qos_conf {
+        v["qos_conf"] = json!(p.qos);
+    }
+    if guids_data {
+        v["guids"] = json!(p.guids);
+    }
+
+    v
+}
@@ -1,68 +1,162 @@
-use std::collections::HashMap;
-
-use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{stream, Stream};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
-use url::Url;
-
-use self::types::{Configuration, PhysicalPort, Port};
-use crate::rest::{RestClient, RestConfig, RestError};
 
+use self::actions::ActionRequest;
+use self::counters::{Snapshot, SnapshotRequest};
+use self::events::Record;
+use self::job::JobReply;
+use crate::cred::{CredentialError, CredentialProvider};
+use crate::guid::{Guid, InvalidGuid};
+use crate::rest::{RestAuth, RestCert, RestClient, RestConfig, RestError, RestFixture, RestPolicy};
+
+mod actions;
+mod counters;
+mod events;
+mod job;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod opensm;
+mod plan;
+mod qos;
+mod reconcile;
+mod snapshot;
+mod topology;
 mod types;
 
-pub use types::PortType;
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct PartitionQoS {
-    // Default 2k; one of 2k or 4k; the MTU of the services.
-    pub mtu_limit: u16,
-    // Default is None, value can be range from 0-15
-    pub service_level: u8,
-    // Default is None, can be one of the following: 2.5, 10, 30, 5, 20, 40, 60, 80, 120, 14, 56, 112, 168, 25, 100, 200, or 300
-    pub rate_limit: f64,
-}
+pub use actions::{PortAction, PortId};
+pub use counters::{PortCounters, PortRates};
+pub use events::{Alarm, Event, EventFilter, Severity};
+pub use job::{Job, JobStatus};
+pub use opensm::{OpenSM, OpenSMConfig};
+pub use plan::{NewPkey, Operation, PartitionState, Pkey, PkeyGuids, Plan, PlanStep};
+pub use qos::{IbMtu, IbRate, PartitionQoS};
+pub use reconcile::{Change, DesiredState, PartitionSpec};
+pub use snapshot::{Conflict, PartitionSnapshot, SNAPSHOT_VERSION};
+pub use topology::{Endpoint, Hop, Topology};
+pub use types::{
+    Configuration, Link, PhysicalPort, Port, PortType, System, SystemType, VirtualPort,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PKeyQoS {
     /// The pkey of Partition.
     pub pkey: String,
-    // Default 2k; one of 2k or 4k; the MTU of the services.
-    pub mtu_limit: u16,
-    // Default is None, value can be range from 0-15
+    pub mtu_limit: IbMtu,
     pub service_level: u8,
-    // Default is None, can be one of the following: 2.5, 10, 30, 5, 20, 40, 60, 80, 120, 14, 56, 112, 168, 25, 100, 200, or 300
-    pub rate_limit: f64,
+    pub rate_limit: IbRate,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum PortMembership {
     Limited,
+    #[default]
     Full,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PortConfig {
     /// The GUID of Port.
-    pub guid: String,
+    pub guid: Guid,
     /// Default false; store the PKey at index 0 of the PKey table of the GUID.
+    #[serde(default)]
     pub index0: bool,
     /// Default is full:
     ///   "full"    - members with full membership can communicate with all hosts (members) within the network/partition
     ///   "limited" - members with limited membership cannot communicate with other members with limited membership.
     ///               However, communication is allowed between every other combination of membership types.
+    #[serde(default)]
     pub membership: PortMembership,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
-pub struct PartitionKey(i32);
+/// The result of binding a group of ports which share the same membership and index0.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BindResult {
+    pub membership: PortMembership,
+    pub index0: bool,
+    /// The GUIDs of the group.
+    pub guids: Vec<Guid>,
+    /// The reason if UFM failed to bind the group, None on success.
+    pub error: Option<String>,
+}
+
+impl BindResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The partition key of InfiniBand: the 15-bit base key of the partition, and the
+/// membership bit (0x8000) which is set for the full members in the pkey tables of the
+/// HCAs and in OpenSM.
+///
+/// The keys are equal if they're of the same partition, whatever the membership bit; the
+/// base key 0x0000 is reserved and invalid.
+#[derive(Debug, Clone, Copy)]
+pub struct PartitionKey {
+    base: u16,
+    full_member: bool,
+}
+
+const MEMBERSHIP_BIT: u16 = 0x8000;
+const DEFAULT_PKEY: u16 = 0x7fff;
 
 impl PartitionKey {
     pub fn is_default(&self) -> bool {
-        self.0 == 0x7fff
+        self.base == DEFAULT_PKEY
+    }
+
+    /// The 15-bit key of the partition.
+    pub fn base(&self) -> u16 {
+        self.base
+    }
+
+    /// Whether the membership bit is set.
+    pub fn is_full_member(&self) -> bool {
+        self.full_member
+    }
+
+    /// The key of the same partition with the membership bit set or cleared.
+    pub fn with_full_member(self, full_member: bool) -> Self {
+        Self {
+            full_member,
+            ..self
+        }
+    }
+
+    /// The 16-bit key with the membership bit, as in the pkey tables.
+    pub fn value(&self) -> u16 {
+        match self.full_member {
+            true => self.base | MEMBERSHIP_BIT,
+            false => self.base,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl PartialEq for PartitionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.base == other.base
+    }
+}
+
+impl Eq for PartitionKey {}
+
+impl Hash for PartitionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base.hash(state);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Partition {
     /// The name of Partition.
     pub name: String,
@@ -74,50 +168,66 @@ pub struct Partition {
     pub qos: Option<PartitionQoS>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Pkey {
-    pkey: String,
-    ip_over_ib: bool,
-    membership: PortMembership,
-    index0: bool,
-    guids: Vec<String>,
-}
-
 const HEX_PRE: &str = "0x";
 
-impl TryFrom<i32> for PartitionKey {
+impl TryFrom<u16> for PartitionKey {
     type Error = UFMError;
 
-    fn try_from(pkey: i32) -> Result<Self, Self::Error> {
-        if pkey != (pkey & 0x7fff) {
-            return Err(UFMError::InvalidPKey(pkey.to_string()));
+    /// The 16-bit key, with or without the membership bit.
+    fn try_from(pkey: u16) -> Result<Self, Self::Error> {
+        let base = pkey & !MEMBERSHIP_BIT;
+        if base == 0 {
+            return Err(UFMError::InvalidPKey(format!("0x{:x}", pkey)));
         }
 
-        Ok(PartitionKey(pkey))
+        Ok(PartitionKey {
+            base,
+            full_member: pkey & MEMBERSHIP_BIT != 0,
+        })
     }
 }
 
-impl TryFrom<String> for PartitionKey {
+impl TryFrom<i32> for PartitionKey {
     type Error = UFMError;
 
-    fn try_from(pkey: String) -> Result<Self, Self::Error> {
-        let pkey = pkey.to_lowercase();
-        let base = if pkey.starts_with(HEX_PRE) { 16 } else { 10 };
-        let p = pkey.trim_start_matches(HEX_PRE);
-        let k = i32::from_str_radix(p, base);
+    fn try_from(pkey: i32) -> Result<Self, Self::Error> {
+        u16::try_from(pkey)
+            .map_err(|_| UFMError::InvalidPKey(pkey.to_string()))
+            .and_then(PartitionKey::try_from)
+    }
+}
+
+impl FromStr for PartitionKey {
+    type Err = UFMError;
+
+    /// The key in hex with the "0x" prefix, or in decimal.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pkey = s.trim().to_lowercase();
+        let k = match pkey.strip_prefix(HEX_PRE) {
+            Some(p) => u16::from_str_radix(p, 16),
+            None => pkey.parse::<u16>(),
+        };
 
         match k {
-            Ok(v) => Ok(PartitionKey(v)),
-            Err(_e) => Err(UFMError::InvalidPKey(pkey.to_string())),
+            Ok(v) => PartitionKey::try_from(v),
+            Err(_e) => Err(UFMError::InvalidPKey(s.to_string())),
         }
     }
 }
 
+impl TryFrom<String> for PartitionKey {
+    type Error = UFMError;
+
+    fn try_from(pkey: String) -> Result<Self, Self::Error> {
+        pkey.parse()
+    }
+}
+
 impl TryFrom<&String> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: &String) -> Result<Self, Self::Error> {
-        PartitionKey::try_from(pkey.to_string())
+        pkey.parse()
     }
 }
 
@@ -125,19 +235,51 @@ impl TryFrom<&str> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: &str) -> Result<Self, Self::Error> {
-        PartitionKey::try_from(pkey.to_string())
+        pkey.parse()
     }
 }
 
-impl ToString for PartitionKey {
-    fn to_string(&self) -> String {
-        format!("0x{:x}", self.0)
+impl Display for PartitionKey {
+    /// The base key of the partition, e.g. `0x5` as UFM takes it; the alternate form `{:#}`
+    /// is the 16-bit key with the membership bit, e.g. `0x8005`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.alternate() {
+            true => f.pad(&format!("0x{:04x}", self.value())),
+            false => f.pad(&format!("0x{:x}", self.base)),
+        }
     }
 }
 
 impl From<PartitionKey> for i32 {
+    /// The base key of the partition.
     fn from(v: PartitionKey) -> i32 {
-        v.0
+        v.base as i32
+    }
+}
+
+impl Serialize for PartitionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for PartitionKey {
+    /// The 16-bit key, or the key in string, e.g. "0x5".
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Key {
+            Number(i64),
+            String(String),
+        }
+
+        match Key::deserialize(deserializer)? {
+            Key::Number(n) => i32::try_from(n)
+                .map_err(|_| UFMError::InvalidPKey(n.to_string()))
+                .and_then(PartitionKey::try_from),
+            Key::String(s) => s.parse(),
+        }
+        .map_err(serde::de::Error::custom)
     }
 }
 
@@ -153,6 +295,15 @@ impl TryFrom<String> for PortMembership {
     }
 }
 
+impl Display for PortMembership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortMembership::Full => f.write_str("full"),
+            PortMembership::Limited => f.write_str("limited"),
+        }
+    }
+}
+
 impl TryFrom<&str> for PortMembership {
     type Error = UFMError;
 
@@ -163,6 +314,7 @@ impl TryFrom<&str> for PortMembership {
 
 pub struct Ufm {
     client: RestClient,
+    wait: Option<Duration>,
 }
 
 #[derive(Error, Debug)]
@@ -173,21 +325,48 @@ pub enum UFMError {
     NotFound(String),
     #[error("invalid pkey '{0}'")]
     InvalidPKey(String),
+    #[error("invalid guid '{0}'")]
+    InvalidGuid(String),
     #[error("invalid configuration '{0}'")]
     InvalidConfig(String),
+    #[error("failed to auth '{0}'")]
+    AuthFailure(String),
+    /// UFM replied an error status other than 401, 403 and 404, with the body of the response.
+    #[error("UFM failed with status {status}: {body}")]
+    Http { status: u16, body: String },
+    /// The job of UFM failed, with the reason from its summary or from its failed sub-jobs.
+    #[error("job {id} failed: {reason}")]
+    JobFailed { id: String, reason: String },
+    /// The job of UFM is not completed in the timeout, with its last progress in percent.
+    #[error("job {id} is not completed in time, at {progress}%")]
+    JobTimeout { id: String, progress: u32 },
 }
 
 impl From<RestError> for UFMError {
     fn from(e: RestError) -> Self {
+        let reason = e.reason().unwrap_or_default();
         match e {
-            RestError::NotFound(msg) => UFMError::NotFound(msg),
-            RestError::AuthFailure(msg) => UFMError::InvalidConfig(msg),
+            RestError::NotFound { .. } => UFMError::NotFound(reason),
+            RestError::AuthFailure { .. } => UFMError::AuthFailure(reason),
+            RestError::Status { status, body, .. } => UFMError::Http { status, body },
             RestError::InvalidConfig(msg) => UFMError::InvalidConfig(msg),
-            _ => UFMError::Unknown(String::from("Unknown Rest Error")),
+            _ => UFMError::Unknown(e.to_string()),
         }
     }
 }
 
+impl From<InvalidGuid> for UFMError {
+    fn from(e: InvalidGuid) -> Self {
+        UFMError::InvalidGuid(e.0)
+    }
+}
+
+impl From<CredentialError> for UFMError {
+    fn from(e: CredentialError) -> Self {
+        UFMError::InvalidConfig(e.to_string())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct UFMCert {
     pub ca_crt: String,
@@ -196,36 +375,211 @@ pub struct UFMCert {
 }
 
 pub struct UFMConfig {
+    /// The URL of UFM, e.g. `https://ufm` or `https://jump:8443`.
     pub address: String,
     pub username: Option<String>,
     pub password: Option<String>,
     pub token: Option<String>,
     pub cert: Option<UFMCert>,
+    /// The name of the credential in the CredentialProvider instead of the inline secret, which
+    /// is the password if username is set or the token otherwise; see UFMConfig::resolve.
+    pub credential: Option<String>,
+    /// The timeouts and retries of the requests to UFM.
+    pub policy: RestPolicy,
+    /// Record the requests to UFM into a fixture file, or replay them from it.
+    pub fixture: Option<RestFixture>,
+    /// Wait up to the timeout for the jobs of the changes which UFM runs in the background,
+    /// e.g. of the pkeys on large fabrics; return once UFM accepts the changes if None.
+    pub wait: Option<Duration>,
+}
+
+impl UFMConfig {
+    /// Replace the credential by its secret from the provider.
+    pub async fn resolve(self, provider: &dyn CredentialProvider) -> Result<Self, UFMError> {
+        let Some(name) = &self.credential else {
+            return Ok(self);
+        };
+
+        let secret = provider.get(name).await?;
+        Ok(match self.username {
+            Some(_) => UFMConfig {
+                password: Some(secret),
+                credential: None,
+                ..self
+            },
+            None => UFMConfig {
+                token: Some(secret),
+                credential: None,
+                ..self
+            },
+        })
+    }
 }
 
+/// The subnet manager which manages the partitions of the fabric, e.g. UFM or OpenSM.
+#[async_trait]
+pub trait SubnetManager: Send + Sync {
+    async fn version(&self) -> Result<String, UFMError>;
+    async fn list_partition(&self) -> Result<Vec<Partition>, UFMError>;
+    async fn get_partition(&self, pkey: &str) -> Result<Partition, UFMError>;
+    /// Create the partition and bind the ports to it, the partition is either created with
+    /// all its ports or not created at all.
+    async fn create_partition(&self, p: Partition, ports: Vec<PortConfig>) -> Result<(), UFMError>;
+    async fn delete_partition(&self, pkey: &str) -> Result<(), UFMError>;
+    async fn update_partition_qos(&self, p: Partition) -> Result<(), UFMError>;
+    /// Bind the ports to the partition, the partition is created if it does not exist; the
+    /// result of every group of ports with the same membership and index0 is reported.
+    async fn bind_ports(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError>;
+    /// Change the IPoIB of the partition and bind the ports to it, as UFM only changes IPoIB
+    /// when binding ports; `bind_ports` keeps the IPoIB of an existing partition of OpenSM.
+    async fn update_partition_ipoib(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError>;
+    async fn unbind_ports(&self, pkey: PartitionKey, guids: Vec<Guid>) -> Result<(), UFMError>;
+    async fn list_port(&self, pkey: PartitionKey) -> Result<Vec<Port>, UFMError>;
+
+    /// Compute the changes to move the partitions to the desired state; the partitions which
+    /// are not declared are deleted if `prune` is true, except the default partition.
+    async fn diff(&self, desired: &DesiredState, prune: bool) -> Result<Vec<Change>, UFMError> {
+        reconcile::diff(self, desired, prune).await
+    }
+
+    /// Apply the changes in order, stop at the first failure.
+    async fn apply(&self, changes: &[Change]) -> Result<(), UFMError> {
+        reconcile::apply(self, changes).await
+    }
+
+    /// Move the partitions to the desired state, and return the applied changes.
+    async fn reconcile(
+        &self,
+        desired: &DesiredState,
+        prune: bool,
+    ) -> Result<Vec<Change>, UFMError> {
+        let changes = self.diff(desired, prune).await?;
+        self.apply(&changes).await?;
+
+        Ok(changes)
+    }
+
+    /// Compute the plan of the changes without applying them.
+    async fn plan(&self, changes: Vec<Change>) -> Result<Plan, UFMError> {
+        plan::plan(self, changes).await
+    }
+
+    /// Take the snapshot of the partitions with their QoS and members, except the default
+    /// partition.
+    async fn export(&self) -> Result<PartitionSnapshot, UFMError> {
+        snapshot::export(self).await
+    }
+
+    /// Compute the changes to recreate the partitions of the snapshot; the partitions which
+    /// already exist are handled by `conflict`.
+    async fn diff_snapshot(
+        &self,
+        snapshot: &PartitionSnapshot,
+        conflict: Conflict,
+    ) -> Result<Vec<Change>, UFMError> {
+        snapshot::diff(self, snapshot, conflict).await
+    }
+
+    /// Recreate the partitions of the snapshot, and return the applied changes.
+    async fn import(
+        &self,
+        snapshot: &PartitionSnapshot,
+        conflict: Conflict,
+    ) -> Result<Vec<Change>, UFMError> {
+        let changes = self.diff_snapshot(snapshot, conflict).await?;
+        self.apply(&changes).await?;
+
+        Ok(changes)
+    }
+
+    /// The requests sent to apply the change, empty if the subnet manager is not managed by
+    /// requests.
+    fn operations(&self, change: &Change) -> Result<Vec<Operation>, UFMError> {
+        change.operations()
+    }
+}
+
+/// The configuration of the subnet manager to build.
+#[allow(clippy::large_enum_variant)]
+pub enum SMConfig {
+    Ufm(UFMConfig),
+    OpenSM(OpenSMConfig),
+}
+
+pub fn build(conf: SMConfig) -> Result<Box<dyn SubnetManager>, UFMError> {
+    match conf {
+        SMConfig::Ufm(conf) => Ok(Box::new(connect(conf)?)),
+        SMConfig::OpenSM(conf) => Ok(Box::new(OpenSM::new(conf))),
+    }
+}
+
+// The REST prefix of UFM for basic authentication.
+const UFM_BASIC_AUTH_PATH: &str = "/ufmRest";
+// The REST prefix of UFM for token authentication.
+const UFM_TOKEN_AUTH_PATH: &str = "/ufmRestV3";
+// The REST prefix of UFM for client certificate authentication.
+const UFM_CERT_AUTH_PATH: &str = "/ufmRestV2";
+// UFM expects the access token in the Authorization header with the "Basic" scheme.
+const UFM_TOKEN_SCHEME: &str = "Basic";
+
 pub fn connect(conf: UFMConfig) -> Result<Ufm, UFMError> {
-    let addr = Url::parse(&conf.address)
-        .map_err(|_| UFMError::InvalidConfig("invalid UFM url".to_string()))?;
-    let address = addr
-        .host_str()
-        .ok_or(UFMError::InvalidConfig("invalid UFM host".to_string()))?;
-
-    let password = conf
-        .password
-        .clone()
-        .ok_or(UFMError::InvalidConfig("password is empty".to_string()))?;
-    let username = conf
-        .username
-        .clone()
-        .ok_or(UFMError::InvalidConfig("username is empty".to_string()))?;
+    if let Some(name) = &conf.credential {
+        return Err(UFMError::InvalidConfig(format!(
+            "credential '{}' is not resolved",
+            name
+        )));
+    }
+
+    let token = conf.token.clone().filter(|t| !t.is_empty());
+
+    let (auth, base_path) = match (token, &conf.cert) {
+        (Some(token), _) => (
+            RestAuth::Token {
+                scheme: UFM_TOKEN_SCHEME.to_string(),
+                token,
+            },
+            UFM_TOKEN_AUTH_PATH,
+        ),
+        (None, Some(_)) => (RestAuth::None, UFM_CERT_AUTH_PATH),
+        (None, None) => {
+            let password = conf
+                .password
+                .clone()
+                .ok_or(UFMError::InvalidConfig("password is empty".to_string()))?;
+            let username = conf
+                .username
+                .clone()
+                .ok_or(UFMError::InvalidConfig("username is empty".to_string()))?;
+
+            (RestAuth::Basic { username, password }, UFM_BASIC_AUTH_PATH)
+        }
+    };
 
     let c = RestClient::new(&RestConfig {
-        address: address.to_string(),
-        password,
-        username,
+        address: conf.address.clone(),
+        base_path: base_path.to_string(),
+        auth,
+        cert: conf.cert.map(|c| RestCert {
+            ca_crt: Some(c.ca_crt),
+            tls_key: c.tls_key,
+            tls_crt: c.tls_crt,
+        }),
+        policy: conf.policy,
+        fixture: conf.fixture,
     })?;
 
-    Ok(Ufm { client: c })
+    Ok(Ufm {
+        client: c,
+        wait: conf.wait,
+    })
 }
 
 impl Ufm {
@@ -236,77 +590,119 @@ impl Ufm {
         Ok(config)
     }
 
-    pub async fn update_partition_qos(&self, p: Partition) -> Result<(), UFMError> {
-        let path = String::from("/resources/pkeys/qos_conf");
-        let qos = p
-            .qos
-            .ok_or(UFMError::InvalidConfig("no partition qos".to_string()))?;
-
-        let qos = PKeyQoS {
-            pkey: p.pkey.to_string(),
-            mtu_limit: qos.mtu_limit,
-            rate_limit: qos.rate_limit,
-            service_level: qos.service_level,
+    /// Send the request of the operation to UFM; wait for its job if UFM runs it in the
+    /// background and UFMConfig::wait is set.
+    pub async fn execute(&self, op: &Operation) -> Result<(), UFMError> {
+        let path = op.path();
+        let reply: serde_json::Value = match op {
+            Operation::AddPartition(p) => self.client.post(&path, p).await?,
+            Operation::AddPorts(p) => self.client.post(&path, p).await?,
+            Operation::RemovePorts(p) => self.client.post(&path, p).await?,
+            Operation::UpdateQoS(p) => self.client.put(&path, p).await?,
+            Operation::DeletePartition(_) => self.client.delete(&path).await?,
         };
 
-        let _ = self.client.put(&path, &qos).await?;
+        // UFM replies an empty body to the changes which are done at once.
+        if let (Some(timeout), Ok(reply)) = (self.wait, JobReply::deserialize(&reply)) {
+            self.job(&reply.job_id).with_timeout(timeout).wait().await?;
+        }
 
         Ok(())
     }
 
-    pub async fn bind_ports(&self, p: Partition, ports: Vec<PortConfig>) -> Result<(), UFMError> {
-        let path = String::from("/resources/pkeys");
+    pub async fn update_partition_qos(&self, p: Partition) -> Result<(), UFMError> {
+        self.execute(&Operation::update_qos(&p)?).await
+    }
 
-        let mut membership = PortMembership::Full;
-        let mut index0 = true;
+    /// Create the partition with its name, IPoIB and QoS, and bind the ports to it.
+    ///
+    /// The partition is deleted if any of the ports failed to bind, so a partition is either
+    /// created with all its ports or not created at all.
+    pub async fn create_partition(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<(), UFMError> {
+        let op = Operation::add_partition(&p)?;
+        let parts = self.list_partition().await?;
+        if parts.iter().any(|part| part.pkey == p.pkey) {
+            return Err(UFMError::InvalidConfig(format!(
+                "partition {} already exists",
+                p.pkey
+            )));
+        }
+
+        self.execute(&op).await?;
 
-        let mut guids = vec![];
-        for pb in ports {
-            membership = pb.membership.clone();
-            index0 = pb.index0;
-            guids.push(pb.guid.to_string());
+        if ports.is_empty() {
+            return Ok(());
         }
 
-        let pkey = Pkey {
-            pkey: p.pkey.clone().to_string(),
-            ip_over_ib: p.ipoib,
-            membership,
-            index0,
-            guids,
+        let pkey = p.pkey;
+        let results = match self.bind_ports(p, ports).await {
+            Ok(results) => results,
+            Err(e) => {
+                self.rollback_partition(pkey).await;
+                return Err(e);
+            }
         };
 
-        let _ = self.client.post(&path, &pkey).await?;
+        let errors: Vec<String> = results.into_iter().filter_map(|r| r.error).collect();
+        if !errors.is_empty() {
+            self.rollback_partition(pkey).await;
+            return Err(UFMError::Unknown(format!(
+                "failed to bind ports to partition {}: {}",
+                pkey,
+                errors.join("; ")
+            )));
+        }
 
         Ok(())
     }
 
-    pub async fn unbind_ports(
-        &self,
-        pkey: PartitionKey,
-        guids: Vec<String>,
-    ) -> Result<(), UFMError> {
-        let path = String::from("/actions/remove_guids_from_pkey");
-
-        #[derive(Serialize, Deserialize, Debug)]
-        struct Pkey {
-            pkey: String,
-            guids: Vec<String>,
+    async fn rollback_partition(&self, pkey: PartitionKey) {
+        if let Err(e) = self.delete_partition(&pkey.to_string()).await {
+            tracing::warn!("Failed to rollback partition {}: {}", pkey.to_string(), e);
         }
+    }
 
-        let pkey = Pkey {
-            pkey: pkey.clone().to_string(),
-            guids,
-        };
+    /// Bind the ports to the partition, the partition is created if it does not exist.
+    ///
+    /// The ports are grouped by their membership and index0, and each group is sent to UFM
+    /// in a separate request; the result of every group is reported in the returned vector.
+    pub async fn bind_ports(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError> {
+        let mut results = vec![];
+        for op in Operation::add_ports(&p, ports) {
+            let error = match self.execute(&op).await {
+                Ok(()) => None,
+                Err(e) => Some(e.to_string()),
+            };
+
+            if let Operation::AddPorts(pkey) = op {
+                results.push(BindResult {
+                    membership: pkey.membership,
+                    index0: pkey.index0,
+                    guids: pkey.guids,
+                    error,
+                });
+            }
+        }
 
-        self.client.post(&path, &pkey).await?;
+        Ok(results)
+    }
 
-        Ok(())
+    pub async fn unbind_ports(&self, pkey: PartitionKey, guids: Vec<Guid>) -> Result<(), UFMError> {
+        self.execute(&Operation::remove_ports(pkey, guids)).await
     }
 
     pub async fn get_partition(&self, pkey: &str) -> Result<Partition, UFMError> {
         let pkey = PartitionKey::try_from(pkey)?;
 
-        let path = format!("/resources/pkeys/{}?qos_conf=true", pkey.to_string());
+        let path = format!("/resources/pkeys/{}?qos_conf=true", pkey);
 
         #[derive(Serialize, Deserialize, Debug)]
         struct Pkey {
@@ -333,7 +729,7 @@ impl Ufm {
         }
 
         let path = String::from("/resources/pkeys?qos_conf=true");
-        let pkey_qos: Vec<(String, Pkey)> = self.client.list(&path).await?;
+        let pkey_qos: HashMap<String, Pkey> = self.client.get(&path).await?;
 
         let mut parts = Vec::new();
 
@@ -345,15 +741,13 @@ impl Ufm {
                 qos: Some(v.qos_conf.clone()),
             });
         }
+        parts.sort_by_key(|p| i32::from(p.pkey));
 
         Ok(parts)
     }
 
     pub async fn delete_partition(&self, pkey: &str) -> Result<(), UFMError> {
-        let path = format!("/resources/pkeys/{}", pkey);
-        self.client.delete(&path).await?;
-
-        Ok(())
+        self.execute(&Operation::delete_partition(pkey)).await
     }
 
     pub async fn list_port(&self, pkey: PartitionKey) -> Result<Vec<Port>, UFMError> {
@@ -366,7 +760,7 @@ impl Ufm {
             pub guids: Vec<PortConfig>,
         }
 
-        let path = format!("resources/pkeys/{}?guids_data=true", pkey.to_string());
+        let path = format!("resources/pkeys/{}?guids_data=true", pkey);
         let pkeywithguids: PkeyWithGUIDs = self.client.get(&path).await?;
 
         // list physical ports
@@ -374,28 +768,29 @@ impl Ufm {
         let physical_ports: Vec<PhysicalPort> = self.client.list(&path).await?;
 
         // list virtual ports
-        // let path = String::from("/resources/vports");
-        // let virtual_ports: Vec<VirtualPort> = self.client.list(&path).await?;
+        let virtual_ports = self.list_vports(None).await?;
 
         let mut port_map = HashMap::new();
         for pport in physical_ports {
-            port_map.insert(pport.guid.clone(), Port::from(pport));
+            port_map.insert(pport.guid, Port::from(pport));
+        }
+        for vport in virtual_ports {
+            port_map.insert(vport.guid, vport);
         }
 
         if !pkey.is_default() {
             for port_config in pkeywithguids.guids {
-                let guid = port_config.guid;
-                match port_map.get(&guid) {
-                    Some(p) => {
-                        res.push(p.clone());
-                    }
-                    None => {
-                        res.push(Port {
-                            guid,
-                            ..Port::default()
-                        });
-                    }
-                }
+                let mut port = match port_map.get(&port_config.guid) {
+                    Some(p) => p.clone(),
+                    None => Port {
+                        guid: port_config.guid,
+                        ..Port::default()
+                    },
+                };
+                port.membership = Some(port_config.membership);
+                port.index0 = Some(port_config.index0);
+
+                res.push(port);
             }
         } else {
             // list all the ports for default pkey(0x7fff)
@@ -404,6 +799,246 @@ impl Ufm {
         Ok(res)
     }
 
+    /// List the systems of the fabric, e.g. the switches, hosts and gateways, optionally only
+    /// the ones of the type.
+    pub async fn list_systems(
+        &self,
+        system_type: Option<SystemType>,
+    ) -> Result<Vec<System>, UFMError> {
+        let path = match system_type {
+            Some(t) => format!("/resources/systems?type={}", t),
+            None => String::from("/resources/systems"),
+        };
+        let systems: Vec<System> = self.client.list(&path).await?;
+
+        Ok(systems
+            .into_iter()
+            .filter(|s| system_type.is_none_or(|t| s.system_type == t))
+            .collect())
+    }
+
+    /// List the links between the ports of the systems.
+    pub async fn list_links(&self) -> Result<Vec<Link>, UFMError> {
+        let path = String::from("/resources/links");
+        let links: Vec<Link> = self.client.list(&path).await?;
+
+        Ok(links)
+    }
+
+    /// The graph of the systems and links of the fabric.
+    pub async fn topology(&self) -> Result<Topology, UFMError> {
+        let systems = self.list_systems(None).await?;
+        let links = self.list_links().await?;
+
+        let path = String::from("/resources/ports?sys_type=Computer");
+        let ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+
+        Ok(Topology::new(systems, links, ports))
+    }
+
+    /// The counters of the port, e.g. its errors and traffic.
+    pub async fn port_counters(&self, guid: Guid) -> Result<PortCounters, UFMError> {
+        self.list_port_counters(&[guid])
+            .await?
+            .pop()
+            .ok_or(UFMError::NotFound(guid.to_string()))
+    }
+
+    /// The counters of the ports in one snapshot of UFM, in the order of the GUIDs; the ports
+    /// which are not monitored by UFM, e.g. the virtual ports, are skipped.
+    pub async fn list_port_counters(&self, guids: &[Guid]) -> Result<Vec<PortCounters>, UFMError> {
+        let path = String::from("/resources/ports");
+        let mut ports: HashMap<Guid, PhysicalPort> = self
+            .client
+            .list::<PhysicalPort>(&path)
+            .await?
+            .into_iter()
+            .map(|p| (p.guid, p))
+            .collect();
+        let ports: Vec<PhysicalPort> = guids.iter().filter_map(|g| ports.remove(g)).collect();
+        if ports.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let path = String::from("/monitoring/snapshot");
+        let request = SnapshotRequest::ports(ports.iter().map(|p| p.name.clone()).collect());
+        let snapshot: Snapshot = self.client.post(&path, &request).await?;
+
+        // The latest sample of the ports, as the timestamps are in seconds.
+        let mut samples = snapshot
+            .into_values()
+            .next_back()
+            .and_then(|mut objects| objects.remove("Port"))
+            .unwrap_or_default();
+
+        Ok(ports
+            .iter()
+            .filter_map(|p| Some(PortCounters::new(p, samples.remove(&p.name)?)))
+            .collect())
+    }
+
+    /// Disable the port, e.g. to isolate a flapping host, and wait for the job of UFM.
+    pub async fn disable_port(&self, port: &PortId) -> Result<(), UFMError> {
+        self.port_action(port, PortAction::Disable).await
+    }
+
+    /// Enable the port, and wait for the job of UFM.
+    pub async fn enable_port(&self, port: &PortId) -> Result<(), UFMError> {
+        self.port_action(port, PortAction::Enable).await
+    }
+
+    /// Reset the port, and wait for the job of UFM.
+    pub async fn reset_port(&self, port: &PortId) -> Result<(), UFMError> {
+        self.port_action(port, PortAction::Reset).await
+    }
+
+    /// Run the action on the port, and wait until its job is completed; the job is failed if
+    /// it's not completed in the timeout of UFMConfig::wait, or in 5 minutes.
+    pub async fn port_action(&self, port: &PortId, action: PortAction) -> Result<(), UFMError> {
+        let path = String::from("/resources/ports");
+        let ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+        let p = ports
+            .iter()
+            .find(|p| port.matches(p))
+            .ok_or(UFMError::NotFound(port.to_string()))?;
+
+        let path = String::from("/actions");
+        let reply: JobReply = self
+            .client
+            .post(&path, &ActionRequest::new(action, p))
+            .await?;
+
+        let job = self.job(&reply.job_id);
+        match self.wait {
+            Some(timeout) => job.with_timeout(timeout).wait().await?,
+            None => job.wait().await?,
+        };
+
+        Ok(())
+    }
+
+    /// The handle of the job of UFM by its ID, e.g. to wait for it.
+    pub fn job(&self, id: &str) -> Job<'_> {
+        Job::new(self, id)
+    }
+
+    /// List the events of UFM which match the filter, by their IDs.
+    pub async fn list_events(&self, filter: &EventFilter) -> Result<Vec<Event>, UFMError> {
+        self.list_records("/app/events", filter).await
+    }
+
+    /// List the open alarms of UFM which match the filter, by their IDs.
+    pub async fn list_alarms(&self, filter: &EventFilter) -> Result<Vec<Alarm>, UFMError> {
+        self.list_records("/app/alarms", filter).await
+    }
+
+    /// The new events which match the filter, which are polled every interval; the current
+    /// ones are skipped, see `list_events`. A failed poll is yielded as an error and retried
+    /// at the next interval, e.g. to wait for the port-down events.
+    pub fn watch_events(
+        &self,
+        filter: EventFilter,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Event, UFMError>> + '_ {
+        self.watch_records("/app/events", filter, interval)
+    }
+
+    /// The alarms which match the filter, as `watch_events`.
+    pub fn watch_alarms(
+        &self,
+        filter: EventFilter,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Alarm, UFMError>> + '_ {
+        self.watch_records("/app/alarms", filter, interval)
+    }
+
+    async fn list_records<T: Record + DeserializeOwned>(
+        &self,
+        path: &str,
+        filter: &EventFilter,
+    ) -> Result<Vec<T>, UFMError> {
+        let mut records: Vec<T> = self.client.list(path).await?;
+        records.retain(|r| filter.matches(r));
+        records.sort_by_key(|r| r.id());
+
+        Ok(records)
+    }
+
+    fn watch_records<T: Record + DeserializeOwned + 'static>(
+        &self,
+        path: &'static str,
+        filter: EventFilter,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<T, UFMError>> + '_ {
+        // The cursor is the last ID which is polled, whether it matches the filter or not;
+        // it's set by the first successful poll, whose records are not yielded.
+        struct Cursor<T> {
+            last: Option<u64>,
+            started: bool,
+            polled: bool,
+            pending: VecDeque<T>,
+        }
+
+        let cursor = Cursor {
+            last: None,
+            started: false,
+            polled: false,
+            pending: VecDeque::new(),
+        };
+        stream::unfold(cursor, move |mut cursor| {
+            let filter = filter.clone();
+            async move {
+                loop {
+                    if let Some(r) = cursor.pending.pop_front() {
+                        return Some((Ok(r), cursor));
+                    }
+
+                    if cursor.polled {
+                        tokio::time::sleep(interval).await;
+                    }
+                    cursor.polled = true;
+
+                    let records = match self.list_records::<T>(path, &EventFilter::default()).await
+                    {
+                        Ok(records) => records,
+                        Err(e) => return Some((Err(e), cursor)),
+                    };
+                    if !cursor.started {
+                        cursor.started = true;
+                        cursor.last = records.iter().map(|r| r.id()).max();
+                        continue;
+                    }
+                    for r in records {
+                        if cursor.last.is_some_and(|last| r.id() <= last) {
+                            continue;
+                        }
+                        cursor.last = Some(r.id());
+                        if filter.matches(&r) {
+                            cursor.pending.push_back(r);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// List the virtual ports (SR-IOV VFs), optionally only the ones of the parent port.
+    pub async fn list_vports(&self, parent_guid: Option<Guid>) -> Result<Vec<Port>, UFMError> {
+        let path = String::from("/resources/vports");
+        let virtual_ports: Vec<VirtualPort> = self.client.list(&path).await?;
+
+        let vports = virtual_ports
+            .into_iter()
+            .filter(|vport| match parent_guid {
+                Some(guid) => vport.port_guid == guid,
+                None => true,
+            })
+            .map(Port::from)
+            .collect();
+
+        Ok(vports)
+    }
+
     pub async fn version(&self) -> Result<String, UFMError> {
         #[derive(Serialize, Deserialize, Debug)]
         struct Version {
@@ -416,3 +1051,54 @@ impl Ufm {
         Ok(v.ufm_release_version)
     }
 }
+
+#[async_trait]
+impl SubnetManager for Ufm {
+    async fn version(&self) -> Result<String, UFMError> {
+        Ufm::version(self).await
+    }
+
+    async fn list_partition(&self) -> Result<Vec<Partition>, UFMError> {
+        Ufm::list_partition(self).await
+    }
+
+    async fn get_partition(&self, pkey: &str) -> Result<Partition, UFMError> {
+        Ufm::get_partition(self, pkey).await
+    }
+
+    async fn create_partition(&self, p: Partition, ports: Vec<PortConfig>) -> Result<(), UFMError> {
+        Ufm::create_partition(self, p, ports).await
+    }
+
+    async fn delete_partition(&self, pkey: &str) -> Result<(), UFMError> {
+        Ufm::delete_partition(self, pkey).await
+    }
+
+    async fn update_partition_qos(&self, p: Partition) -> Result<(), UFMError> {
+        Ufm::update_partition_qos(self, p).await
+    }
+
+    async fn bind_ports(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError> {
+        Ufm::bind_ports(self, p, ports).await
+    }
+
+    async fn update_partition_ipoib(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError> {
+        Ufm::bind_ports(self, p, ports).await
+    }
+
+    async fn unbind_ports(&self, pkey: PartitionKey, guids: Vec<Guid>) -> Result<(), UFMError> {
+        Ufm::unbind_ports(self, pkey, guids).await
+    }
+
+    async fn list_port(&self, pkey: PartitionKey) -> Result<Vec<Port>, UFMError> {
+        Ufm::list_port(self, pkey).await
+    }
+}
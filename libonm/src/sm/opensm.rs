@@ -0,0 +1,775 @@
+use std::fmt::{self, Display};
+use std::io::ErrorKind;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use tokio::process::Command;
+
+use super::types::Port;
+use super::{
+    BindResult, Change, IbMtu, IbRate, Operation, Partition, PartitionKey, PartitionQoS,
+    PortConfig, PortMembership, SubnetManager, UFMError,
+};
+use crate::guid::Guid;
+
+// The default path of the partition configuration of OpenSM.
+const OPENSM_PARTITIONS: &str = "/etc/opensm/partitions.conf";
+// OpenSM re-reads its configuration files on SIGHUP.
+const OPENSM_REREAD_COMMAND: &str = "pkill -HUP -x opensm";
+
+// The MTU codes of OpenSM for the MTU limits.
+const MTU_CODES: [(IbMtu, u8); 2] = [(IbMtu::Mtu2K, 4), (IbMtu::Mtu4K, 5)];
+// The rate codes of OpenSM for the rate limits.
+const RATE_CODES: [(IbRate, u8); 17] = [
+    (IbRate::Rate2_5, 2),
+    (IbRate::Rate10, 3),
+    (IbRate::Rate30, 4),
+    (IbRate::Rate5, 5),
+    (IbRate::Rate20, 6),
+    (IbRate::Rate40, 7),
+    (IbRate::Rate60, 8),
+    (IbRate::Rate80, 9),
+    (IbRate::Rate120, 10),
+    (IbRate::Rate14, 11),
+    (IbRate::Rate56, 12),
+    (IbRate::Rate112, 13),
+    (IbRate::Rate168, 14),
+    (IbRate::Rate25, 15),
+    (IbRate::Rate100, 16),
+    (IbRate::Rate200, 17),
+    (IbRate::Rate300, 18),
+];
+
+#[derive(Clone, Debug)]
+pub struct OpenSMConfig {
+    /// The path of the partitions.conf of OpenSM.
+    pub partitions: String,
+    /// The command to make OpenSM re-read partitions.conf after it's changed; the file is
+    /// only written if None.
+    pub reread_command: Option<String>,
+}
+
+impl Default for OpenSMConfig {
+    fn default() -> Self {
+        Self {
+            partitions: OPENSM_PARTITIONS.to_string(),
+            reread_command: Some(OPENSM_REREAD_COMMAND.to_string()),
+        }
+    }
+}
+
+/// The subnet manager backed by the partitions.conf of OpenSM.
+///
+/// The keyword members (e.g. `ALL`) and the flags which are not managed here (e.g. `mgid`,
+/// `scope`) are kept as is when the file is written; the comments are kept too, but the ones
+/// inside an entry or after it on its last line are moved above the entry. OpenSM sets index0
+/// per partition by the `indx0` flag, so the ports of a partition share the same index0.
+///
+/// The changes hold an advisory lock on `partitions.conf.lock` next to the file from loading
+/// it to saving it, so the concurrent changes, e.g. of two smctl, are not lost.
+pub struct OpenSM {
+    conf: OpenSMConfig,
+}
+
+impl OpenSM {
+    pub fn new(conf: OpenSMConfig) -> Self {
+        Self { conf }
+    }
+
+    /// Lock partitions.conf for a change until the returned file is dropped.
+    async fn lock(&self) -> Result<std::fs::File, UFMError> {
+        let path = format!("{}.lock", self.conf.partitions);
+        let lock_path = path.clone();
+        let res = tokio::task::spawn_blocking(move || {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)?;
+            file.lock()?;
+            Ok::<_, std::io::Error>(file)
+        })
+        .await
+        .map_err(|e| UFMError::Unknown(e.to_string()))?;
+
+        res.map_err(|e| UFMError::Unknown(format!("failed to lock {}: {}", path, e)))
+    }
+
+    async fn load(&self) -> Result<PartitionsConf, UFMError> {
+        match tokio::fs::read_to_string(&self.conf.partitions).await {
+            Ok(s) => s.parse(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(PartitionsConf::default()),
+            Err(e) => Err(UFMError::Unknown(format!(
+                "failed to read {}: {}",
+                self.conf.partitions, e
+            ))),
+        }
+    }
+
+    /// Replace partitions.conf atomically, and trigger OpenSM to re-read it.
+    async fn save(&self, parts: &PartitionsConf) -> Result<(), UFMError> {
+        let path = &self.conf.partitions;
+        let tmp = format!("{}.tmp", path);
+
+        tokio::fs::write(&tmp, parts.to_string())
+            .await
+            .map_err(|e| UFMError::Unknown(format!("failed to write {}: {}", tmp, e)))?;
+        tokio::fs::rename(&tmp, path)
+            .await
+            .map_err(|e| UFMError::Unknown(format!("failed to write {}: {}", path, e)))?;
+
+        let Some(cmd) = &self.conf.reread_command else {
+            return Ok(());
+        };
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .output()
+            .await
+            .map_err(|e| UFMError::Unknown(format!("failed to run <{}>: {}", cmd, e)))?;
+        if !output.status.success() {
+            return Err(UFMError::Unknown(format!(
+                "failed to run <{}>: {}",
+                cmd,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SubnetManager for OpenSM {
+    async fn version(&self) -> Result<String, UFMError> {
+        let output = Command::new("opensm")
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| UFMError::Unknown(format!("failed to run opensm: {}", e)))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout
+            .lines()
+            .find(|l| l.contains("OpenSM"))
+            .unwrap_or(stdout.trim());
+
+        Ok(version.trim().to_string())
+    }
+
+    async fn list_partition(&self) -> Result<Vec<Partition>, UFMError> {
+        let parts = self.load().await?;
+
+        let mut res = vec![];
+        for entry in &parts.entries {
+            if let Some(p) = entry.partition()? {
+                res.push(p);
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn get_partition(&self, pkey: &str) -> Result<Partition, UFMError> {
+        let pkey = PartitionKey::try_from(pkey)?;
+        let parts = self.load().await?;
+
+        parts
+            .find(pkey)
+            .ok_or(UFMError::NotFound(pkey.to_string()))?
+            .partition()?
+            .ok_or(UFMError::NotFound(pkey.to_string()))
+    }
+
+    async fn create_partition(&self, p: Partition, ports: Vec<PortConfig>) -> Result<(), UFMError> {
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        if parts.find(p.pkey).is_some() {
+            return Err(UFMError::InvalidConfig(format!(
+                "partition {} already exists",
+                p.pkey
+            )));
+        }
+
+        let mut entry = Entry::new(&p)?;
+        let errors: Vec<String> = entry
+            .bind(ports)
+            .into_iter()
+            .filter_map(|r| r.error)
+            .collect();
+        if !errors.is_empty() {
+            return Err(UFMError::Unknown(format!(
+                "failed to bind ports to partition {}: {}",
+                p.pkey,
+                errors.join("; ")
+            )));
+        }
+
+        parts.entries.push(entry);
+        self.save(&parts).await
+    }
+
+    async fn delete_partition(&self, pkey: &str) -> Result<(), UFMError> {
+        let pkey = PartitionKey::try_from(pkey)?;
+        if pkey.is_default() {
+            return Err(UFMError::InvalidConfig(
+                "the default partition can not be deleted".to_string(),
+            ));
+        }
+
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        let len = parts.entries.len();
+        parts.entries.retain(|e| e.pkey != Some(pkey));
+        if parts.entries.len() == len {
+            return Err(UFMError::NotFound(pkey.to_string()));
+        }
+
+        self.save(&parts).await
+    }
+
+    async fn update_partition_qos(&self, p: Partition) -> Result<(), UFMError> {
+        let qos = p
+            .qos
+            .ok_or(UFMError::InvalidConfig("no partition qos".to_string()))?;
+
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        parts
+            .find_mut(p.pkey)
+            .ok_or(UFMError::NotFound(p.pkey.to_string()))?
+            .set_qos(&qos)?;
+
+        self.save(&parts).await
+    }
+
+    async fn bind_ports(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError> {
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        if parts.find(p.pkey).is_none() {
+            parts.entries.push(Entry::new(&p)?);
+        }
+
+        // The ipoib flag of an existing partition is kept, see update_partition_ipoib.
+        let results = parts
+            .find_mut(p.pkey)
+            .ok_or(UFMError::NotFound(p.pkey.to_string()))?
+            .bind(ports);
+
+        if results.iter().any(|r| r.is_ok()) {
+            self.save(&parts).await?;
+        }
+
+        Ok(results)
+    }
+
+    async fn update_partition_ipoib(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<Vec<BindResult>, UFMError> {
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        let entry = parts
+            .find_mut(p.pkey)
+            .ok_or(UFMError::NotFound(p.pkey.to_string()))?;
+        entry.set_flag("ipoib", None, p.ipoib);
+        let results = entry.bind(ports);
+
+        self.save(&parts).await?;
+
+        Ok(results)
+    }
+
+    async fn unbind_ports(&self, pkey: PartitionKey, guids: Vec<Guid>) -> Result<(), UFMError> {
+        let _lock = self.lock().await?;
+        let mut parts = self.load().await?;
+        parts
+            .find_mut(pkey)
+            .ok_or(UFMError::NotFound(pkey.to_string()))?
+            .members
+            .retain(|m| !matches!(m.guid(), Some(g) if guids.contains(&g)));
+
+        self.save(&parts).await
+    }
+
+    async fn list_port(&self, pkey: PartitionKey) -> Result<Vec<Port>, UFMError> {
+        let parts = self.load().await?;
+        let entry = parts
+            .find(pkey)
+            .ok_or(UFMError::NotFound(pkey.to_string()))?;
+
+        let defmember = match entry.flag("defmember") {
+            Some(m) => m.parse()?,
+            None => MemberType::Limited,
+        };
+        let index0 = entry.flag("indx0").is_some();
+
+        let ports = entry
+            .members
+            .iter()
+            .filter_map(|m| {
+                m.guid().map(|guid| Port {
+                    guid,
+                    membership: Some(m.membership.unwrap_or(defmember).into()),
+                    index0: Some(index0),
+                    ..Port::default()
+                })
+            })
+            .collect();
+
+        Ok(ports)
+    }
+
+    fn operations(&self, _change: &Change) -> Result<Vec<Operation>, UFMError> {
+        Ok(vec![])
+    }
+}
+
+/// The partitions.conf of OpenSM, a partition per entry:
+///
+/// ```text
+/// # The comments before the partition.
+/// storage=0x0005, ipoib, mtu=5, rate=7, sl=0 :
+///     0x1070fd0300176625=full,
+///     0x0011223344560200=limited;
+/// ```
+#[derive(Debug, Clone, Default)]
+struct PartitionsConf {
+    entries: Vec<Entry>,
+    /// The comments after the last entry.
+    trailer: Vec<String>,
+}
+
+impl PartitionsConf {
+    fn find(&self, pkey: PartitionKey) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.pkey == Some(pkey))
+    }
+
+    fn find_mut(&mut self, pkey: PartitionKey) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.pkey == Some(pkey))
+    }
+}
+
+impl FromStr for PartitionsConf {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = PartitionsConf::default();
+        let mut comments = vec![];
+        let mut stmt = String::new();
+
+        for line in s.lines() {
+            let mut text = match line.split_once('#') {
+                Some((text, comment)) => {
+                    comments.push(format!("#{}", comment.trim_end()));
+                    text
+                }
+                None => line,
+            };
+
+            while let Some((head, tail)) = text.split_once(';') {
+                stmt.push_str(head);
+                if !stmt.trim().is_empty() {
+                    parts
+                        .entries
+                        .push(Entry::parse(&stmt, comments.split_off(0))?);
+                }
+                stmt.clear();
+                text = tail;
+            }
+
+            stmt.push_str(text);
+            stmt.push('\n');
+        }
+
+        if !stmt.trim().is_empty() {
+            return Err(UFMError::InvalidConfig(format!(
+                "partition <{}> is not terminated by ';'",
+                stmt.trim()
+            )));
+        }
+        parts.trailer = comments;
+
+        Ok(parts)
+    }
+}
+
+impl Display for PartitionsConf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", entry)?;
+        }
+
+        for comment in &self.trailer {
+            writeln!(f, "{}", comment)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A partition of partitions.conf: `name[=pkey][, flag[=value]]... : [member[=type]], ... ;`
+#[derive(Debug, Clone)]
+struct Entry {
+    /// The comments before the entry.
+    comments: Vec<String>,
+    name: String,
+    /// None if the pkey is generated by OpenSM.
+    pkey: Option<PartitionKey>,
+    flags: Vec<(String, Option<String>)>,
+    members: Vec<Member>,
+}
+
+impl Entry {
+    fn new(p: &Partition) -> Result<Self, UFMError> {
+        let name = match p.name.is_empty() {
+            true => format!("pkey_0x{:04x}", p.pkey.base()),
+            false => p.name.clone(),
+        };
+        if name.contains(|c: char| c.is_whitespace() || "=,:;#".contains(c)) {
+            return Err(UFMError::InvalidConfig(format!(
+                "invalid partition name <{}>",
+                name
+            )));
+        }
+
+        let mut entry = Entry {
+            comments: vec![],
+            name,
+            pkey: Some(p.pkey),
+            flags: vec![],
+            members: vec![],
+        };
+        entry.set_flag("ipoib", None, p.ipoib);
+        if let Some(qos) = &p.qos {
+            entry.set_qos(qos)?;
+        }
+
+        Ok(entry)
+    }
+
+    fn parse(stmt: &str, comments: Vec<String>) -> Result<Self, UFMError> {
+        let invalid = || UFMError::InvalidConfig(format!("invalid partition <{}>", stmt.trim()));
+
+        // The flags, e.g. mgid=ff12:401b::1, may contain ':' but the members never do.
+        let (header, members) = stmt.rsplit_once(':').ok_or_else(invalid)?;
+        let mut header = header.split(',').map(str::trim);
+
+        let first = header.next().ok_or_else(invalid)?;
+        let (name, pkey) = match first.split_once('=') {
+            Some((name, pkey)) => {
+                // The high bit is the membership in the old format, e.g. 0x8001.
+                let pkey = PartitionKey::try_from(pkey.trim())?.with_full_member(false);
+                (name.trim(), Some(pkey))
+            }
+            None => (first, None),
+        };
+
+        let flags = header
+            .filter(|f| !f.is_empty())
+            .map(|f| match f.split_once('=') {
+                Some((k, v)) => (k.trim().to_string(), Some(v.trim().to_string())),
+                None => (f.to_string(), None),
+            })
+            .collect();
+
+        let members = members
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(|m| match m.split_once('=') {
+                Some((guid, t)) => Ok(Member {
+                    guid: guid.trim().to_string(),
+                    membership: Some(t.parse()?),
+                }),
+                None => Ok(Member {
+                    guid: m.to_string(),
+                    membership: None,
+                }),
+            })
+            .collect::<Result<Vec<Member>, UFMError>>()?;
+
+        Ok(Entry {
+            comments,
+            name: name.to_string(),
+            pkey,
+            flags,
+            members,
+        })
+    }
+
+    /// The partition of the entry, None if its pkey is generated by OpenSM.
+    fn partition(&self) -> Result<Option<Partition>, UFMError> {
+        let Some(pkey) = self.pkey else {
+            return Ok(None);
+        };
+
+        Ok(Some(Partition {
+            name: self.name.clone(),
+            pkey,
+            ipoib: self.flag("ipoib").is_some(),
+            qos: self.qos()?,
+        }))
+    }
+
+    /// The QoS of the entry, None if none of mtu, rate and sl is set; the default of UFM is
+    /// used for the unset ones.
+    fn qos(&self) -> Result<Option<PartitionQoS>, UFMError> {
+        let (mtu, rate, sl) = (self.flag("mtu"), self.flag("rate"), self.flag("sl"));
+        if mtu.is_none() && rate.is_none() && sl.is_none() {
+            return Ok(None);
+        }
+
+        let mut qos = PartitionQoS::default();
+        if let Some(mtu) = mtu {
+            let code = parse_code("mtu", mtu)?;
+            qos.mtu_limit = MTU_CODES
+                .iter()
+                .find(|(_, c)| *c == code)
+                .map(|(m, _)| *m)
+                .ok_or(UFMError::InvalidConfig(format!(
+                    "unsupported mtu '{}'",
+                    mtu
+                )))?;
+        }
+        if let Some(rate) = rate {
+            let code = parse_code("rate", rate)?;
+            qos.rate_limit = RATE_CODES
+                .iter()
+                .find(|(_, c)| *c == code)
+                .map(|(r, _)| *r)
+                .ok_or(UFMError::InvalidConfig(format!(
+                    "unsupported rate '{}'",
+                    rate
+                )))?;
+        }
+        if let Some(sl) = sl {
+            qos.service_level = parse_code("sl", sl)?;
+        }
+
+        Ok(Some(qos))
+    }
+
+    fn set_qos(&mut self, qos: &PartitionQoS) -> Result<(), UFMError> {
+        qos.validate()?;
+        let mtu = MTU_CODES
+            .iter()
+            .find(|(m, _)| *m == qos.mtu_limit)
+            .map(|(_, c)| *c)
+            .unwrap_or_default();
+        let rate = RATE_CODES
+            .iter()
+            .find(|(r, _)| *r == qos.rate_limit)
+            .map(|(_, c)| *c)
+            .unwrap_or_default();
+
+        self.set_flag("mtu", Some(mtu.to_string()), true);
+        self.set_flag("rate", Some(rate.to_string()), true);
+        self.set_flag("sl", Some(qos.service_level.to_string()), true);
+
+        Ok(())
+    }
+
+    /// The value of the flag, an empty string if the flag has no value.
+    fn flag(&self, key: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_deref().unwrap_or_default())
+    }
+
+    /// Set the flag in place, or remove it if `enabled` is false.
+    fn set_flag(&mut self, key: &str, value: Option<String>, enabled: bool) {
+        let pos = self
+            .flags
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case(key));
+        match (pos, enabled) {
+            (Some(i), true) => self.flags[i].1 = value,
+            (None, true) => self.flags.push((key.to_string(), value)),
+            (Some(i), false) => {
+                self.flags.remove(i);
+            }
+            (None, false) => {}
+        }
+    }
+
+    /// Add the ports to the members, grouped by their membership and index0 as UFM does.
+    ///
+    /// The `indx0` flag is only changed if the partition has no port yet, so the groups
+    /// with a different index0 than the other ports of the partition are failed.
+    fn bind(&mut self, ports: Vec<PortConfig>) -> Vec<BindResult> {
+        let mut results: Vec<BindResult> = vec![];
+        for port in ports {
+            match results
+                .iter_mut()
+                .find(|r| r.membership == port.membership && r.index0 == port.index0)
+            {
+                Some(r) => r.guids.push(port.guid),
+                None => results.push(BindResult {
+                    membership: port.membership,
+                    index0: port.index0,
+                    guids: vec![port.guid],
+                    error: None,
+                }),
+            }
+        }
+
+        for r in results.iter_mut() {
+            let index0 = self.flag("indx0").is_some();
+            if r.index0 != index0 {
+                if self.members.iter().any(|m| m.guid().is_some()) {
+                    r.error = Some(format!(
+                        "index0 is {} for the ports of partition {} in OpenSM",
+                        index0,
+                        self.pkey.map(|p| p.to_string()).unwrap_or_default()
+                    ));
+                    continue;
+                }
+                self.set_flag("indx0", None, r.index0);
+            }
+
+            let membership = MemberType::from(r.membership.clone());
+            for guid in r.guids.iter().copied() {
+                match self.members.iter_mut().find(|m| m.guid() == Some(guid)) {
+                    // "both" is kept for full, as it's full in the partition too.
+                    Some(m)
+                        if m.membership == Some(MemberType::Both)
+                            && membership == MemberType::Full => {}
+                    Some(m) => m.membership = Some(membership),
+                    None => self.members.push(Member {
+                        guid: format!("{:#}", guid),
+                        membership: Some(membership),
+                    }),
+                }
+            }
+        }
+
+        results
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
+
+        write!(f, "{}", self.name)?;
+        if let Some(pkey) = self.pkey {
+            write!(f, "=0x{:04x}", pkey.base())?;
+        }
+        for (k, v) in &self.flags {
+            match v {
+                Some(v) => write!(f, ", {}={}", k, v)?,
+                None => write!(f, ", {}", k)?,
+            }
+        }
+        write!(f, " :")?;
+
+        // OpenSM reads the file by lines of limited length, so a member per line.
+        for (i, m) in self.members.iter().enumerate() {
+            let sep = if i + 1 < self.members.len() { "," } else { "" };
+            write!(f, "\n    {}{}", m, sep)?;
+        }
+
+        writeln!(f, ";")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    /// The GUID with the "0x" prefix, or a keyword, e.g. ALL, SELF, ALL_SWITCHES.
+    guid: String,
+    /// None for the `defmember` of the partition.
+    membership: Option<MemberType>,
+}
+
+impl Member {
+    /// The GUID of the member, None for the keywords.
+    fn guid(&self) -> Option<Guid> {
+        if !self.guid.to_lowercase().starts_with("0x") {
+            return None;
+        }
+        self.guid.parse().ok()
+    }
+}
+
+impl Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.membership {
+            Some(m) => write!(f, "{}={}", self.guid, m),
+            None => write!(f, "{}", self.guid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MemberType {
+    Full,
+    Limited,
+    Both,
+}
+
+impl FromStr for MemberType {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "full" => Ok(MemberType::Full),
+            "limited" => Ok(MemberType::Limited),
+            "both" => Ok(MemberType::Both),
+            _ => Err(UFMError::InvalidConfig(format!(
+                "invalid membership '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+impl Display for MemberType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemberType::Full => f.write_str("full"),
+            MemberType::Limited => f.write_str("limited"),
+            MemberType::Both => f.write_str("both"),
+        }
+    }
+}
+
+impl From<PortMembership> for MemberType {
+    fn from(m: PortMembership) -> Self {
+        match m {
+            PortMembership::Full => MemberType::Full,
+            PortMembership::Limited => MemberType::Limited,
+        }
+    }
+}
+
+impl From<MemberType> for PortMembership {
+    fn from(m: MemberType) -> Self {
+        match m {
+            MemberType::Full | MemberType::Both => PortMembership::Full,
+            MemberType::Limited => PortMembership::Limited,
+        }
+    }
+}
+
+fn parse_code(flag: &str, value: &str) -> Result<u8, UFMError> {
+    let v = value.trim().to_lowercase();
+    let code = match v.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => v.parse(),
+    };
+
+    code.map_err(|_| UFMError::InvalidConfig(format!("invalid {} '{}'", flag, value)))
+}
@@ -0,0 +1,329 @@
+use std::fmt::{self, Display};
+
+use http::Method;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+
+use super::types::Port;
+use super::{
+    Change, IbMtu, IbRate, PKeyQoS, Partition, PartitionKey, PortConfig, PortMembership,
+    SubnetManager, UFMError,
+};
+use crate::guid::Guid;
+
+/// The payload to create a partition without ports.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewPkey {
+    pub pkey: String,
+    /// The name of Partition.
+    pub partition: String,
+    pub ip_over_ib: bool,
+    pub mtu_limit: IbMtu,
+    pub service_level: u8,
+    pub rate_limit: IbRate,
+}
+
+/// The payload to add ports with the same membership and index0 to a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pkey {
+    pub pkey: String,
+    pub ip_over_ib: bool,
+    pub membership: PortMembership,
+    pub index0: bool,
+    pub guids: Vec<Guid>,
+}
+
+/// The payload to remove ports from a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PkeyGuids {
+    pub pkey: String,
+    pub guids: Vec<Guid>,
+}
+
+/// A request sent by Ufm to change the partitions.
+#[derive(Debug, Clone)]
+pub enum Operation {
+    AddPartition(NewPkey),
+    AddPorts(Pkey),
+    RemovePorts(PkeyGuids),
+    UpdateQoS(PKeyQoS),
+    DeletePartition(String),
+}
+
+impl Operation {
+    pub fn method(&self) -> Method {
+        match self {
+            Operation::AddPartition(_) | Operation::AddPorts(_) | Operation::RemovePorts(_) => {
+                Method::POST
+            }
+            Operation::UpdateQoS(_) => Method::PUT,
+            Operation::DeletePartition(_) => Method::DELETE,
+        }
+    }
+
+    pub fn path(&self) -> String {
+        match self {
+            Operation::AddPartition(_) => String::from("/resources/pkeys/add"),
+            Operation::AddPorts(_) => String::from("/resources/pkeys"),
+            Operation::RemovePorts(_) => String::from("/actions/remove_guids_from_pkey"),
+            Operation::UpdateQoS(_) => String::from("/resources/pkeys/qos_conf"),
+            Operation::DeletePartition(pkey) => format!("/resources/pkeys/{}", pkey),
+        }
+    }
+
+    pub(super) fn add_partition(p: &Partition) -> Result<Self, UFMError> {
+        let qos = p.qos.clone().unwrap_or_default();
+        qos.validate()?;
+
+        Ok(Operation::AddPartition(NewPkey {
+            pkey: p.pkey.to_string(),
+            partition: p.name.clone(),
+            ip_over_ib: p.ipoib,
+            mtu_limit: qos.mtu_limit,
+            service_level: qos.service_level,
+            rate_limit: qos.rate_limit,
+        }))
+    }
+
+    /// The ports are grouped by their membership and index0, one request per group; the
+    /// partition is created by a request without ports if there's no port.
+    pub(super) fn add_ports(p: &Partition, ports: Vec<PortConfig>) -> Vec<Self> {
+        let mut groups: Vec<Pkey> = vec![];
+        for pb in ports {
+            match groups
+                .iter_mut()
+                .find(|g| g.membership == pb.membership && g.index0 == pb.index0)
+            {
+                Some(g) => g.guids.push(pb.guid),
+                None => groups.push(Pkey {
+                    pkey: p.pkey.to_string(),
+                    ip_over_ib: p.ipoib,
+                    membership: pb.membership,
+                    index0: pb.index0,
+                    guids: vec![pb.guid],
+                }),
+            }
+        }
+
+        if groups.is_empty() {
+            groups.push(Pkey {
+                pkey: p.pkey.to_string(),
+                ip_over_ib: p.ipoib,
+                membership: PortMembership::Full,
+                index0: true,
+                guids: vec![],
+            });
+        }
+
+        groups.into_iter().map(Operation::AddPorts).collect()
+    }
+
+    pub(super) fn remove_ports(pkey: PartitionKey, guids: Vec<Guid>) -> Self {
+        Operation::RemovePorts(PkeyGuids {
+            pkey: pkey.to_string(),
+            guids,
+        })
+    }
+
+    pub(super) fn update_qos(p: &Partition) -> Result<Self, UFMError> {
+        let qos = p
+            .qos
+            .clone()
+            .ok_or(UFMError::InvalidConfig("no partition qos".to_string()))?;
+        qos.validate()?;
+
+        Ok(Operation::UpdateQoS(PKeyQoS {
+            pkey: p.pkey.to_string(),
+            mtu_limit: qos.mtu_limit,
+            rate_limit: qos.rate_limit,
+            service_level: qos.service_level,
+        }))
+    }
+
+    pub(super) fn delete_partition(pkey: &str) -> Self {
+        Operation::DeletePartition(pkey.to_string())
+    }
+
+    fn payload(&self) -> Result<serde_json::Value, serde_json::Error> {
+        match self {
+            Operation::AddPartition(p) => serde_json::to_value(p),
+            Operation::AddPorts(p) => serde_json::to_value(p),
+            Operation::RemovePorts(p) => serde_json::to_value(p),
+            Operation::UpdateQoS(p) => serde_json::to_value(p),
+            Operation::DeletePartition(_) => Ok(serde_json::Value::Null),
+        }
+    }
+}
+
+impl Serialize for Operation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let payload = self.payload().map_err(serde::ser::Error::custom)?;
+
+        let mut s = serializer.serialize_struct("Operation", 3)?;
+        s.serialize_field("method", self.method().as_str())?;
+        s.serialize_field("path", &self.path())?;
+        s.serialize_field("payload", &payload)?;
+        s.end()
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method(), self.path())?;
+        match self.payload() {
+            Ok(serde_json::Value::Null) => Ok(()),
+            Ok(payload) => write!(f, " {}", payload),
+            // A formatting error panics in format! and println!, so keep the line instead.
+            Err(_) => write!(f, " <unserializable payload>"),
+        }
+    }
+}
+
+impl Change {
+    /// The pkey of the partition changed by this change.
+    pub fn pkey(&self) -> PartitionKey {
+        match self {
+            Change::CreatePartition { partition, .. }
+            | Change::UpdateQoS { partition }
+            | Change::UpdateIPoIB { partition, .. }
+            | Change::BindPorts { partition, .. } => partition.pkey,
+            Change::UnbindPorts { pkey, .. } | Change::DeletePartition { pkey } => *pkey,
+        }
+    }
+
+    /// The requests which Ufm sends for this change.
+    pub fn operations(&self) -> Result<Vec<Operation>, UFMError> {
+        let ops = match self {
+            Change::CreatePartition { partition, ports } => {
+                let mut ops = vec![Operation::add_partition(partition)?];
+                if !ports.is_empty() {
+                    ops.extend(Operation::add_ports(partition, ports.clone()));
+                }
+                ops
+            }
+            Change::UpdateQoS { partition } => vec![Operation::update_qos(partition)?],
+            Change::UpdateIPoIB { partition, ports } | Change::BindPorts { partition, ports } => {
+                Operation::add_ports(partition, ports.clone())
+            }
+            Change::UnbindPorts { pkey, guids } => {
+                vec![Operation::remove_ports(*pkey, guids.clone())]
+            }
+            Change::DeletePartition { pkey } => {
+                vec![Operation::delete_partition(&pkey.to_string())]
+            }
+        };
+
+        Ok(ops)
+    }
+}
+
+/// The current state of a partition changed by a plan.
+#[derive(Serialize, Debug, Clone)]
+pub struct PartitionState {
+    pub pkey: PartitionKey,
+    /// None if the partition does not exist.
+    pub partition: Option<Partition>,
+    pub ports: Vec<Port>,
+}
+
+/// A change of a plan with the requests to apply it.
+#[derive(Serialize, Debug, Clone)]
+pub struct PlanStep {
+    pub change: Change,
+    /// The requests to UFM; empty if the subnet manager is not managed by requests.
+    pub operations: Vec<Operation>,
+}
+
+/// The changes to the partitions and the exact requests of them, which are computed
+/// without being applied.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Plan {
+    pub current: Vec<PartitionState>,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for state in &self.current {
+            let Some(p) = &state.partition else {
+                writeln!(f, "Partition {}: not found", state.pkey)?;
+                continue;
+            };
+
+            writeln!(f, "Partition {} <{}>", p.pkey, p.name)?;
+            writeln!(f, "    {:15}: {}", "IPoIB", p.ipoib)?;
+            if let Some(qos) = &p.qos {
+                writeln!(f, "    {:15}: {}", "MTU", qos.mtu_limit)?;
+                writeln!(f, "    {:15}: {}", "Rate Limit", qos.rate_limit)?;
+                writeln!(f, "    {:15}: {}", "Service Level", qos.service_level)?;
+            }
+            writeln!(f, "    {:15}: ", "Ports")?;
+            for port in &state.ports {
+                let membership = match &port.membership {
+                    Some(m) => m.to_string(),
+                    None => "-".to_string(),
+                };
+                let index0 = match port.index0 {
+                    Some(i) => i.to_string(),
+                    None => "-".to_string(),
+                };
+                writeln!(
+                    f,
+                    "        {:<20}{:<10}{:<10}",
+                    port.guid, membership, index0
+                )?;
+            }
+        }
+
+        if self.steps.is_empty() {
+            return writeln!(f, "\nNo changes.");
+        }
+
+        writeln!(f)?;
+        for step in &self.steps {
+            writeln!(f, "{}", step.change)?;
+            for op in &step.operations {
+                writeln!(f, "    {}", op)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the plan of the changes with the current state of the changed partitions.
+pub(super) async fn plan<S: SubnetManager + ?Sized>(
+    sm: &S,
+    changes: Vec<Change>,
+) -> Result<Plan, UFMError> {
+    let parts = sm.list_partition().await?;
+
+    let mut current: Vec<PartitionState> = vec![];
+    for change in &changes {
+        let pkey = change.pkey();
+        if current.iter().any(|s| s.pkey == pkey) {
+            continue;
+        }
+
+        let partition = parts.iter().find(|p| p.pkey == pkey).cloned();
+        let ports = match partition {
+            Some(_) => sm.list_port(pkey).await?,
+            None => vec![],
+        };
+
+        current.push(PartitionState {
+            pkey,
+            partition,
+            ports,
+        });
+    }
+
+    let mut steps = vec![];
+    for change in changes {
+        steps.push(PlanStep {
+            operations: sm.operations(&change)?,
+            change,
+        });
+    }
+
+    Ok(Plan { current, steps })
+}
@@ -0,0 +1,293 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::UFMError;
+
+// The max service level of InfiniBand.
+const MAX_SERVICE_LEVEL: u8 = 15;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PartitionQoS {
+    /// Default 2k; the MTU of the services.
+    pub mtu_limit: IbMtu,
+    /// Default 0; one of 0-15.
+    pub service_level: u8,
+    /// Default 2.5 Gb/s; the rate limit of the services.
+    pub rate_limit: IbRate,
+}
+
+impl Default for PartitionQoS {
+    fn default() -> Self {
+        Self {
+            mtu_limit: IbMtu::Mtu2K,
+            service_level: 0,
+            rate_limit: IbRate::Rate2_5,
+        }
+    }
+}
+
+impl PartitionQoS {
+    /// Check the QoS before it's sent to the subnet manager; the MTU and rate limit are
+    /// checked by their types, except the unsupported ones replied by UFM.
+    pub fn validate(&self) -> Result<(), UFMError> {
+        if let IbMtu::Other(_) = self.mtu_limit {
+            return Err(UFMError::InvalidConfig(format!(
+                "unsupported mtu '{}', it should be 2k or 4k",
+                self.mtu_limit
+            )));
+        }
+        if let IbRate::Other(_) = self.rate_limit {
+            return Err(UFMError::InvalidConfig(format!(
+                "unsupported rate limit '{}'",
+                self.rate_limit
+            )));
+        }
+        if self.service_level > MAX_SERVICE_LEVEL {
+            return Err(UFMError::InvalidConfig(format!(
+                "invalid service level '{}', it should be 0-{}",
+                self.service_level, MAX_SERVICE_LEVEL
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The MTU limit of a partition, which UFM takes in KB, e.g. `4` for 4096 bytes.
+///
+/// It's parsed from "4k", "4096" or "4".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IbMtu {
+    #[default]
+    Mtu2K,
+    Mtu4K,
+    /// The MTU in KB which UFM replied but is not supported, so the partition is still
+    /// listed; it's rejected by PartitionQoS::validate.
+    Other(u16),
+}
+
+impl IbMtu {
+    /// The MTU in KB, the wire format of UFM.
+    pub fn kilobytes(&self) -> u16 {
+        match self {
+            IbMtu::Mtu2K => 2,
+            IbMtu::Mtu4K => 4,
+            IbMtu::Other(kb) => *kb,
+        }
+    }
+
+    /// The MTU in bytes.
+    pub fn bytes(&self) -> u32 {
+        self.kilobytes() as u32 * 1024
+    }
+}
+
+impl TryFrom<u16> for IbMtu {
+    type Error = UFMError;
+
+    /// The MTU in KB or in bytes.
+    fn try_from(mtu: u16) -> Result<Self, Self::Error> {
+        match mtu {
+            2 | 2048 => Ok(IbMtu::Mtu2K),
+            4 | 4096 => Ok(IbMtu::Mtu4K),
+            _ => Err(UFMError::InvalidConfig(format!(
+                "unsupported mtu '{}', it should be 2k or 4k",
+                mtu
+            ))),
+        }
+    }
+}
+
+impl FromStr for IbMtu {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mtu = s.trim().to_lowercase();
+        let mtu = mtu
+            .strip_suffix("kb")
+            .or(mtu.strip_suffix('k'))
+            .unwrap_or(&mtu);
+
+        mtu.parse::<u16>()
+            .map_err(|_| UFMError::InvalidConfig(format!("invalid mtu '{}'", s)))
+            .and_then(IbMtu::try_from)
+    }
+}
+
+impl Display for IbMtu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}k", self.kilobytes())
+    }
+}
+
+impl Serialize for IbMtu {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.kilobytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for IbMtu {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(n)
+                if n.fract() == 0.0 && (0.0..=u16::MAX as f64).contains(&n) =>
+            {
+                Ok(IbMtu::try_from(n as u16).unwrap_or(IbMtu::Other(n as u16)))
+            }
+            NumberOrString::Number(n) => {
+                Err(UFMError::InvalidConfig(format!("invalid mtu '{}'", n)))
+            }
+            NumberOrString::String(s) => s.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The rate limit of a partition in Gb/s, e.g. `100` for 4x EDR.
+///
+/// It's parsed from the rate, e.g. "200", "200g" or "2.5", or from the name of the 4x link,
+/// e.g. "HDR" for 200 Gb/s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IbRate {
+    #[default]
+    Rate2_5,
+    Rate5,
+    Rate10,
+    Rate14,
+    Rate20,
+    Rate25,
+    Rate30,
+    Rate40,
+    Rate56,
+    Rate60,
+    Rate80,
+    Rate100,
+    Rate112,
+    Rate120,
+    Rate168,
+    Rate200,
+    Rate300,
+    /// The rate in Mb/s which UFM replied but is not supported, so the partition is still
+    /// listed; it's rejected by PartitionQoS::validate.
+    Other(u32),
+}
+
+// The rates in Gb/s.
+const IB_RATES: [(IbRate, f64); 17] = [
+    (IbRate::Rate2_5, 2.5),
+    (IbRate::Rate5, 5.0),
+    (IbRate::Rate10, 10.0),
+    (IbRate::Rate14, 14.0),
+    (IbRate::Rate20, 20.0),
+    (IbRate::Rate25, 25.0),
+    (IbRate::Rate30, 30.0),
+    (IbRate::Rate40, 40.0),
+    (IbRate::Rate56, 56.0),
+    (IbRate::Rate60, 60.0),
+    (IbRate::Rate80, 80.0),
+    (IbRate::Rate100, 100.0),
+    (IbRate::Rate112, 112.0),
+    (IbRate::Rate120, 120.0),
+    (IbRate::Rate168, 168.0),
+    (IbRate::Rate200, 200.0),
+    (IbRate::Rate300, 300.0),
+];
+
+// The rates of the 4x links by their names.
+const IB_RATE_NAMES: [(&str, IbRate); 6] = [
+    ("sdr", IbRate::Rate10),
+    ("ddr", IbRate::Rate20),
+    ("qdr", IbRate::Rate40),
+    ("fdr", IbRate::Rate56),
+    ("edr", IbRate::Rate100),
+    ("hdr", IbRate::Rate200),
+];
+
+impl IbRate {
+    /// The rate in Gb/s, the wire format of UFM.
+    pub fn gbps(&self) -> f64 {
+        if let IbRate::Other(mbps) = self {
+            return *mbps as f64 / 1000.0;
+        }
+
+        IB_RATES
+            .iter()
+            .find(|(r, _)| r == self)
+            .map(|(_, gbps)| *gbps)
+            .unwrap_or_default()
+    }
+}
+
+impl TryFrom<f64> for IbRate {
+    type Error = UFMError;
+
+    /// The rate in Gb/s.
+    fn try_from(gbps: f64) -> Result<Self, Self::Error> {
+        IB_RATES
+            .iter()
+            .find(|(_, r)| (r - gbps).abs() < 0.01)
+            .map(|(rate, _)| *rate)
+            .ok_or(UFMError::InvalidConfig(format!(
+                "unsupported rate limit '{}'",
+                gbps
+            )))
+    }
+}
+
+impl FromStr for IbRate {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rate = s.trim().to_lowercase();
+        if let Some((_, r)) = IB_RATE_NAMES.iter().find(|(name, _)| *name == rate) {
+            return Ok(*r);
+        }
+
+        let rate = ["gb/s", "gbps", "g"]
+            .iter()
+            .find_map(|suffix| rate.strip_suffix(suffix))
+            .unwrap_or(&rate);
+        rate.trim()
+            .parse::<f64>()
+            .map_err(|_| UFMError::InvalidConfig(format!("invalid rate limit '{}'", s)))
+            .and_then(IbRate::try_from)
+    }
+}
+
+impl Display for IbRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.gbps())
+    }
+}
+
+impl Serialize for IbRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.gbps())
+    }
+}
+
+impl<'de> Deserialize<'de> for IbRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(n) => IbRate::try_from(n).or_else(|e| {
+                let mbps = (n * 1000.0).round();
+                match mbps > 0.0 && mbps <= u32::MAX as f64 {
+                    true => Ok(IbRate::Other(mbps as u32)),
+                    false => Err(e),
+                }
+            }),
+            NumberOrString::String(s) => s.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+// UFM replies the numbers, and the strings are accepted for the configuration files, e.g. "4k".
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f64),
+    String(String),
+}
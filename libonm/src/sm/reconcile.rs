@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::Port;
+use super::{Partition, PartitionKey, PartitionQoS, PortConfig, SubnetManager, UFMError};
+use crate::guid::Guid;
+
+/// The desired state of the partitions, e.g. in TOML:
+///
+/// ```toml
+/// [[partition]]
+/// pkey = "0x5"
+/// name = "storage"
+/// ipoib = true
+/// qos = { mtu_limit = 4, service_level = 0, rate_limit = 100.0 }
+/// members = [
+///     { guid = "1070fd0300176625", membership = "full", index0 = true },
+///     { guid = "0011223344560200", membership = "limited" },
+/// ]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DesiredState {
+    #[serde(default, rename = "partition")]
+    pub partitions: Vec<PartitionSpec>,
+}
+
+/// The desired state of a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartitionSpec {
+    /// The pkey of Partition, e.g. "0x5".
+    pub pkey: String,
+    /// The name of Partition; it's only used when the partition is created.
+    #[serde(default)]
+    pub name: String,
+    /// Default false
+    #[serde(default)]
+    pub ipoib: bool,
+    /// The QoS of Partition; it's left as is if None.
+    pub qos: Option<PartitionQoS>,
+    /// The ports of Partition; the other ports are unbound from the partition.
+    #[serde(default)]
+    pub members: Vec<PortConfig>,
+}
+
+/// A change to move the partitions of UFM to the desired state.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case", tag = "change")]
+pub enum Change {
+    CreatePartition {
+        partition: Partition,
+        ports: Vec<PortConfig>,
+    },
+    UpdateQoS {
+        partition: Partition,
+    },
+    /// Change the IPoIB of the partition, UFM only changes it when the ports are bound.
+    #[serde(rename = "update_ipoib")]
+    UpdateIPoIB {
+        partition: Partition,
+        ports: Vec<PortConfig>,
+    },
+    BindPorts {
+        partition: Partition,
+        ports: Vec<PortConfig>,
+    },
+    UnbindPorts {
+        pkey: PartitionKey,
+        guids: Vec<Guid>,
+    },
+    DeletePartition {
+        pkey: PartitionKey,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::CreatePartition { partition, ports } => write!(
+                f,
+                "+ partition {} <{}> with {} ports",
+                partition.pkey,
+                partition.name,
+                ports.len()
+            ),
+            Change::UpdateQoS { partition } => {
+                let qos = partition.qos.clone().unwrap_or_default();
+                write!(
+                    f,
+                    "~ partition {} qos: mtu {}, service level {}, rate limit {}",
+                    partition.pkey, qos.mtu_limit, qos.service_level, qos.rate_limit
+                )
+            }
+            Change::UpdateIPoIB { partition, .. } => write!(
+                f,
+                "~ partition {} ipoib: {}",
+                partition.pkey, partition.ipoib
+            ),
+            Change::BindPorts { partition, ports } => {
+                let ports: Vec<String> = ports
+                    .iter()
+                    .map(|p| format!("{}:{}", p.guid, p.membership))
+                    .collect();
+                write!(
+                    f,
+                    "+ partition {} ports: {}",
+                    partition.pkey,
+                    ports.join(", ")
+                )
+            }
+            Change::UnbindPorts { pkey, guids } => write!(
+                f,
+                "- partition {} ports: {}",
+                pkey,
+                guids
+                    .iter()
+                    .map(|g| g.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Change::DeletePartition { pkey } => write!(f, "- partition {}", pkey),
+        }
+    }
+}
+
+impl DesiredState {
+    pub fn from_toml(s: &str) -> Result<Self, UFMError> {
+        toml::from_str(s).map_err(|e| UFMError::InvalidConfig(e.to_string()))
+    }
+
+    /// Compute the changes from the current partitions and their ports; the ports are only
+    /// required for the declared partitions.
+    fn diff(
+        &self,
+        current: &[Partition],
+        ports: &HashMap<PartitionKey, Vec<Port>>,
+        prune: bool,
+    ) -> Result<Vec<Change>, UFMError> {
+        let mut changes = vec![];
+        let mut declared = HashSet::new();
+
+        for spec in &self.partitions {
+            let partition = spec.partition()?;
+            if partition.pkey.is_default() {
+                return Err(UFMError::InvalidConfig(
+                    "the default partition can not be declared".to_string(),
+                ));
+            }
+            if !declared.insert(partition.pkey) {
+                return Err(UFMError::InvalidConfig(format!(
+                    "partition {} is declared more than once",
+                    partition.pkey
+                )));
+            }
+
+            let Some(cur) = current.iter().find(|p| p.pkey == partition.pkey) else {
+                changes.push(Change::CreatePartition {
+                    partition,
+                    ports: spec.members.clone(),
+                });
+                continue;
+            };
+
+            if !spec.name.is_empty() && cur.name != spec.name {
+                tracing::warn!(
+                    "Partition {} can not be renamed from <{}> to <{}>",
+                    partition.pkey.to_string(),
+                    cur.name,
+                    spec.name
+                );
+            }
+
+            if spec.qos.is_some() && cur.qos != spec.qos {
+                changes.push(Change::UpdateQoS {
+                    partition: partition.clone(),
+                });
+            }
+
+            let cur_ports: HashMap<Guid, &Port> = ports
+                .get(&partition.pkey)
+                .into_iter()
+                .flatten()
+                .map(|p| (p.guid, p))
+                .collect();
+            let guids: HashSet<Guid> = spec.members.iter().map(|m| m.guid).collect();
+
+            let unbind: Vec<Guid> = cur_ports
+                .keys()
+                .filter(|guid| !guids.contains(*guid))
+                .copied()
+                .collect();
+            if !unbind.is_empty() {
+                changes.push(Change::UnbindPorts {
+                    pkey: partition.pkey,
+                    guids: unbind,
+                });
+            }
+
+            // IPoIB is set when binding ports, so all the ports are re-bound if it's changed.
+            if cur.ipoib != spec.ipoib {
+                if spec.members.is_empty() {
+                    tracing::warn!(
+                        "IPoIB of partition {} can not be changed without ports",
+                        partition.pkey.to_string()
+                    );
+                } else {
+                    changes.push(Change::UpdateIPoIB {
+                        partition,
+                        ports: spec.members.clone(),
+                    });
+                    continue;
+                }
+            }
+
+            let bind: Vec<PortConfig> = spec
+                .members
+                .iter()
+                .filter(|m| match cur_ports.get(&m.guid) {
+                    Some(p) => {
+                        p.membership.as_ref() != Some(&m.membership) || p.index0 != Some(m.index0)
+                    }
+                    None => true,
+                })
+                .cloned()
+                .collect();
+            if !bind.is_empty() {
+                changes.push(Change::BindPorts {
+                    partition,
+                    ports: bind,
+                });
+            }
+        }
+
+        if prune {
+            for p in current {
+                if !p.pkey.is_default() && !declared.contains(&p.pkey) {
+                    changes.push(Change::DeletePartition { pkey: p.pkey });
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+impl PartitionSpec {
+    pub(super) fn partition(&self) -> Result<Partition, UFMError> {
+        if let Some(qos) = &self.qos {
+            qos.validate()?;
+        }
+
+        Ok(Partition {
+            name: self.name.clone(),
+            pkey: PartitionKey::try_from(&self.pkey)?,
+            ipoib: self.ipoib,
+            qos: self.qos.clone(),
+        })
+    }
+}
+
+/// Compute the changes to move the partitions of the subnet manager to the desired state.
+pub(super) async fn diff<S: SubnetManager + ?Sized>(
+    sm: &S,
+    desired: &DesiredState,
+    prune: bool,
+) -> Result<Vec<Change>, UFMError> {
+    let current = sm.list_partition().await?;
+
+    let mut ports = HashMap::new();
+    for spec in &desired.partitions {
+        let pkey = PartitionKey::try_from(&spec.pkey)?;
+        if !pkey.is_default() && current.iter().any(|p| p.pkey == pkey) {
+            ports.insert(pkey, sm.list_port(pkey).await?);
+        }
+    }
+
+    desired.diff(&current, &ports, prune)
+}
+
+/// Apply the changes in order, stop at the first failure.
+pub(super) async fn apply<S: SubnetManager + ?Sized>(
+    sm: &S,
+    changes: &[Change],
+) -> Result<(), UFMError> {
+    for change in changes {
+        match change {
+            Change::CreatePartition { partition, ports } => {
+                sm.create_partition(partition.clone(), ports.clone())
+                    .await?
+            }
+            Change::UpdateQoS { partition } => sm.update_partition_qos(partition.clone()).await?,
+            Change::UpdateIPoIB { partition, ports } => {
+                let results = sm
+                    .update_partition_ipoib(partition.clone(), ports.clone())
+                    .await?;
+                if let Some(e) = results.into_iter().find_map(|r| r.error) {
+                    return Err(UFMError::Unknown(e));
+                }
+            }
+            Change::BindPorts { partition, ports } => {
+                let results = sm.bind_ports(partition.clone(), ports.clone()).await?;
+                if let Some(e) = results.into_iter().find_map(|r| r.error) {
+                    return Err(UFMError::Unknown(e));
+                }
+            }
+            Change::UnbindPorts { pkey, guids } => sm.unbind_ports(*pkey, guids.clone()).await?,
+            Change::DeletePartition { pkey } => sm.delete_partition(&pkey.to_string()).await?,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{reconcile, Change, DesiredState, PartitionSpec, PortConfig, SubnetManager, UFMError};
+
+/// The version of the snapshot format written by this library.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The snapshot of the partitions of a subnet manager, e.g. a backup before an upgrade of UFM;
+/// the default partition is not in the snapshot, as it's managed by the subnet manager. In
+/// TOML:
+///
+/// ```toml
+/// version = 1
+/// source = "6.11.1-2"
+///
+/// [[partition]]
+/// pkey = "0x5"
+/// name = "storage"
+/// ipoib = true
+/// qos = { mtu_limit = 4, service_level = 0, rate_limit = 100.0 }
+/// members = [
+///     { guid = "1070fd0300176625", membership = "full", index0 = true },
+/// ]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartitionSnapshot {
+    /// The version of the format, see SNAPSHOT_VERSION.
+    pub version: u32,
+    /// The version of the subnet manager the snapshot was taken from.
+    #[serde(default)]
+    pub source: String,
+    #[serde(default, rename = "partition")]
+    pub partitions: Vec<PartitionSpec>,
+}
+
+impl PartitionSnapshot {
+    pub fn from_json(s: &str) -> Result<Self, UFMError> {
+        let snapshot: Self =
+            serde_json::from_str(s).map_err(|e| UFMError::InvalidConfig(e.to_string()))?;
+        snapshot.validate()?;
+
+        Ok(snapshot)
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, UFMError> {
+        let snapshot: Self =
+            toml::from_str(s).map_err(|e| UFMError::InvalidConfig(e.to_string()))?;
+        snapshot.validate()?;
+
+        Ok(snapshot)
+    }
+
+    pub fn to_json(&self) -> Result<String, UFMError> {
+        serde_json::to_string_pretty(self).map_err(|e| UFMError::Unknown(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, UFMError> {
+        toml::to_string(self).map_err(|e| UFMError::Unknown(e.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), UFMError> {
+        if self.version == 0 || self.version > SNAPSHOT_VERSION {
+            return Err(UFMError::InvalidConfig(format!(
+                "unsupported snapshot version {}, expected {}",
+                self.version, SNAPSHOT_VERSION
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// What to do with a partition of the snapshot which already exists when it's imported.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Conflict {
+    /// Import nothing if any of the partitions exists.
+    #[default]
+    Fail,
+    /// Leave the existing partitions as they are.
+    Skip,
+    /// Move the existing partitions to their QoS and members in the snapshot.
+    Overwrite,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::Fail => f.pad("fail"),
+            Conflict::Skip => f.pad("skip"),
+            Conflict::Overwrite => f.pad("overwrite"),
+        }
+    }
+}
+
+impl FromStr for Conflict {
+    type Err = UFMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fail" => Ok(Conflict::Fail),
+            "skip" => Ok(Conflict::Skip),
+            "overwrite" => Ok(Conflict::Overwrite),
+            _ => Err(UFMError::InvalidConfig(format!("invalid conflict '{}'", s))),
+        }
+    }
+}
+
+/// Take the snapshot of the partitions, except the default partition.
+pub(super) async fn export<S: SubnetManager + ?Sized>(
+    sm: &S,
+) -> Result<PartitionSnapshot, UFMError> {
+    let mut partitions = vec![];
+    for p in sm.list_partition().await? {
+        if p.pkey.is_default() {
+            continue;
+        }
+
+        let members = sm
+            .list_port(p.pkey)
+            .await?
+            .into_iter()
+            .map(|port| PortConfig {
+                guid: port.guid,
+                index0: port.index0.unwrap_or_default(),
+                membership: port.membership.unwrap_or_default(),
+            })
+            .collect();
+
+        partitions.push(PartitionSpec {
+            pkey: p.pkey.to_string(),
+            name: p.name,
+            ipoib: p.ipoib,
+            qos: p.qos,
+            members,
+        });
+    }
+
+    Ok(PartitionSnapshot {
+        version: SNAPSHOT_VERSION,
+        source: sm.version().await?,
+        partitions,
+    })
+}
+
+/// Compute the changes to recreate the partitions of the snapshot, by the conflict policy of
+/// the existing partitions.
+pub(super) async fn diff<S: SubnetManager + ?Sized>(
+    sm: &S,
+    snapshot: &PartitionSnapshot,
+    conflict: Conflict,
+) -> Result<Vec<Change>, UFMError> {
+    snapshot.validate()?;
+
+    let current = sm.list_partition().await?;
+    let mut partitions = vec![];
+    for spec in &snapshot.partitions {
+        let pkey = spec.partition()?.pkey;
+        if !current.iter().any(|p| p.pkey == pkey) {
+            partitions.push(spec.clone());
+            continue;
+        }
+
+        match conflict {
+            Conflict::Fail => {
+                return Err(UFMError::InvalidConfig(format!(
+                    "partition {} already exists",
+                    pkey
+                )))
+            }
+            Conflict::Skip => tracing::info!("Partition {} exists, skipped", pkey.to_string()),
+            Conflict::Overwrite => partitions.push(spec.clone()),
+        }
+    }
+
+    reconcile::diff(sm, &DesiredState { partitions }, false).await
+}
@@ -0,0 +1,226 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{self, Display, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{Link, PhysicalPort, System, SystemType};
+use crate::guid::Guid;
+
+/// The graph of the fabric: the systems are the nodes and the links are the edges, e.g. to
+/// find the leaf and spine switches of a host.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Topology {
+    pub systems: Vec<System>,
+    pub links: Vec<Link>,
+    /// The system GUID of the host ports, as the links of the hosts are by port GUID.
+    #[serde(default)]
+    pub ports: BTreeMap<Guid, Guid>,
+}
+
+/// A port of a system at one end of a hop.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    /// The GUID of the system.
+    pub guid: Guid,
+    /// The name of the system, or its GUID if it's not known.
+    pub name: String,
+    pub port: u32,
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.name, self.port)
+    }
+}
+
+/// A link from one system to another.
+#[derive(Serialize, Debug, Clone)]
+pub struct Hop {
+    pub from: Endpoint,
+    pub to: Endpoint,
+    pub width: String,
+    pub speed: String,
+    pub state: String,
+}
+
+impl Display for Hop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+impl Topology {
+    pub fn new(systems: Vec<System>, links: Vec<Link>, ports: Vec<PhysicalPort>) -> Self {
+        let ports = ports
+            .into_iter()
+            .filter_map(|p| Some((p.guid, p.system_id.parse().ok()?)))
+            .collect();
+
+        Self {
+            systems,
+            links,
+            ports,
+        }
+    }
+
+    /// The system of the GUID, which is of the system or of one of its ports.
+    pub fn system(&self, guid: Guid) -> Option<&System> {
+        let guid = self.system_guid(guid);
+        self.systems.iter().find(|s| s.guid == guid)
+    }
+
+    /// Find the system by its name, or by the GUID of the system or of one of its ports.
+    pub fn find(&self, system: &str) -> Option<&System> {
+        if let Ok(guid) = system.parse::<Guid>() {
+            if let Some(s) = self.system(guid) {
+                return Some(s);
+            }
+        }
+
+        self.systems
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(system))
+    }
+
+    /// The hops from the system to its peers, one per link.
+    pub fn hops(&self, system: Guid) -> Vec<Hop> {
+        let system = self.system_guid(system);
+
+        let mut hops = vec![];
+        for link in &self.links {
+            let src = self.endpoint(link.source_guid, link.source_port);
+            let dst = self.endpoint(link.destination_guid, link.destination_port);
+            let (from, to) = match (src, dst) {
+                (src, dst) if src.guid == system => (src, dst),
+                (src, dst) if dst.guid == system => (dst, src),
+                _ => continue,
+            };
+
+            hops.push(Hop {
+                from,
+                to,
+                width: link.width.clone(),
+                speed: link.speed.clone(),
+                state: link.state.clone(),
+            });
+        }
+
+        hops
+    }
+
+    /// The tier of each system: 0 for the hosts, 1 for the leaf switches, 2 for the spine
+    /// switches and so on; the systems which are not connected to any host are not included.
+    pub fn tiers(&self) -> HashMap<Guid, usize> {
+        let mut tiers = HashMap::new();
+        let mut queue = VecDeque::new();
+        for s in &self.systems {
+            if s.system_type == SystemType::Host {
+                tiers.insert(s.guid, 0);
+                queue.push_back(s.guid);
+            }
+        }
+
+        while let Some(guid) = queue.pop_front() {
+            let tier = tiers[&guid];
+            for hop in self.hops(guid) {
+                if let Entry::Vacant(e) = tiers.entry(hop.to.guid) {
+                    e.insert(tier + 1);
+                    queue.push_back(hop.to.guid);
+                }
+            }
+        }
+
+        tiers
+    }
+
+    /// The paths from the system up to the top tier of the fabric, e.g. from a host to its
+    /// leaf switches and then to the spine switches; a path per combination of links.
+    pub fn uplinks(&self, system: Guid) -> Vec<Vec<Hop>> {
+        let tiers = self.tiers();
+        let mut paths = vec![];
+        self.walk_up(self.system_guid(system), &tiers, &mut vec![], &mut paths);
+
+        paths
+    }
+
+    fn walk_up(
+        &self,
+        system: Guid,
+        tiers: &HashMap<Guid, usize>,
+        path: &mut Vec<Hop>,
+        paths: &mut Vec<Vec<Hop>>,
+    ) {
+        let tier = tiers.get(&system).copied().unwrap_or_default();
+        let ups: Vec<Hop> = self
+            .hops(system)
+            .into_iter()
+            .filter(|hop| tiers.get(&hop.to.guid).is_some_and(|t| *t > tier))
+            .collect();
+
+        if ups.is_empty() {
+            if !path.is_empty() {
+                paths.push(path.clone());
+            }
+            return;
+        }
+
+        for hop in ups {
+            let next = hop.to.guid;
+            path.push(hop);
+            self.walk_up(next, tiers, path, paths);
+            path.pop();
+        }
+    }
+
+    /// The graph in the DOT language of Graphviz, e.g. for `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph fabric {\n");
+        for s in &self.systems {
+            let shape = match s.system_type {
+                SystemType::Switch => "box",
+                _ => "ellipse",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", shape={}];",
+                s.guid,
+                escape(&s.name),
+                shape
+            );
+        }
+
+        for link in &self.links {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -- \"{}\" [taillabel=\"{}\", headlabel=\"{}\", label=\"{}\"];",
+                self.system_guid(link.source_guid),
+                self.system_guid(link.destination_guid),
+                link.source_port,
+                link.destination_port,
+                escape(format!("{} {}", link.width, link.speed).trim())
+            );
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    fn system_guid(&self, guid: Guid) -> Guid {
+        self.ports.get(&guid).copied().unwrap_or(guid)
+    }
+
+    fn endpoint(&self, guid: Guid, port: u32) -> Endpoint {
+        let guid = self.system_guid(guid);
+        let name = match self.systems.iter().find(|s| s.guid == guid) {
+            Some(s) => s.name.clone(),
+            None => guid.to_string(),
+        };
+
+        Endpoint { guid, name, port }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -1,4 +1,9 @@
-use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::PortMembership;
+use crate::guid::Guid;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -10,20 +15,24 @@ pub enum PortType {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub struct Port {
-    pub guid: String,
+    pub guid: Guid,
     pub name: Option<String>,
     pub system_id: String,
     pub lid: i32,
     pub system_name: String,
     pub logical_state: String,
-    pub parent_guid: Option<String>,
+    pub parent_guid: Option<Guid>,
     pub port_type: Option<PortType>,
+    /// The membership of the port in the partition, None if unknown.
+    pub membership: Option<PortMembership>,
+    /// Whether the pkey is at index 0 of the pkey table of the port, None if unknown.
+    pub index0: Option<bool>,
 }
 
 impl Default for Port {
     fn default() -> Self {
         Self {
-            guid: "".to_string(),
+            guid: Guid::default(),
             name: None,
             system_id: "".to_string(),
             lid: 65535,
@@ -31,6 +40,8 @@ impl Default for Port {
             logical_state: "Unknown".to_string(),
             parent_guid: None,
             port_type: None,
+            membership: None,
+            index0: None,
         }
     }
 }
@@ -38,7 +49,7 @@ impl Default for Port {
 impl From<PhysicalPort> for Port {
     fn from(physicalport: PhysicalPort) -> Self {
         Port {
-            guid: physicalport.guid.clone(),
+            guid: physicalport.guid,
             name: Some(physicalport.name),
             system_id: physicalport.system_id,
             lid: physicalport.lid,
@@ -46,6 +57,8 @@ impl From<PhysicalPort> for Port {
             logical_state: physicalport.logical_state,
             parent_guid: None,
             port_type: Some(PortType::Physical),
+            membership: None,
+            index0: None,
         }
     }
 }
@@ -53,7 +66,7 @@ impl From<PhysicalPort> for Port {
 impl From<VirtualPort> for Port {
     fn from(virtualport: VirtualPort) -> Self {
         Port {
-            guid: virtualport.virtual_port_guid.clone(),
+            guid: virtualport.virtual_port_guid,
             name: None,
             system_id: virtualport.system_guid,
             lid: virtualport.virtual_port_lid,
@@ -61,29 +74,40 @@ impl From<VirtualPort> for Port {
             logical_state: virtualport.virtual_port_state,
             parent_guid: Some(virtualport.port_guid),
             port_type: Some(PortType::Virtual),
+            membership: None,
+            index0: None,
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PhysicalPort {
-    pub guid: String,
+    pub guid: Guid,
     pub name: String,
     #[serde(rename = "systemID")]
     pub system_id: String,
     pub lid: i32,
     pub system_name: String,
     pub logical_state: String,
+    /// The number of the port in its system, starting from 1.
+    #[serde(default)]
+    pub number: u32,
+    /// The active speed of the link of the port, e.g. `HDR`.
+    #[serde(default)]
+    pub active_speed: String,
+    /// The active width of the link of the port, e.g. `4x`.
+    #[serde(default)]
+    pub active_width: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VirtualPort {
-    pub virtual_port_guid: String,
+    pub virtual_port_guid: Guid,
     pub system_guid: String,
     pub virtual_port_lid: i32,
     pub system_name: String,
     pub virtual_port_state: String,
-    pub port_guid: String,
+    pub port_guid: Guid,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -96,3 +120,81 @@ pub struct Configuration {
     pub log_file: String,
     pub qos: i32,
 }
+
+/// The type of a system in the fabric.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemType {
+    Switch,
+    #[serde(alias = "computer")]
+    Host,
+    Gateway,
+    Router,
+    #[serde(other)]
+    Unknown,
+}
+
+impl Display for SystemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemType::Switch => f.pad("switch"),
+            SystemType::Host => f.pad("host"),
+            SystemType::Gateway => f.pad("gateway"),
+            SystemType::Router => f.pad("router"),
+            SystemType::Unknown => f.pad("unknown"),
+        }
+    }
+}
+
+/// A system in the fabric, e.g. a switch or a host.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct System {
+    /// The GUID of the system, which is the `system_id` of its ports.
+    #[serde(alias = "system_guid")]
+    pub guid: Guid,
+    #[serde(rename = "system_name")]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub system_type: SystemType,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub ip: String,
+}
+
+/// A cable between the ports of two systems.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Link {
+    /// The GUID of the source system, or of the source port for the hosts.
+    pub source_guid: Guid,
+    /// The number of the source port, starting from 1.
+    #[serde(deserialize_with = "port_number")]
+    pub source_port: u32,
+    pub destination_guid: Guid,
+    #[serde(deserialize_with = "port_number")]
+    pub destination_port: u32,
+    /// The width of the link, e.g. `IB_4x`.
+    #[serde(default)]
+    pub width: String,
+    /// The speed of the link, e.g. `HDR`.
+    #[serde(default)]
+    pub speed: String,
+    /// The state of the link, e.g. `Active`.
+    #[serde(default)]
+    pub state: String,
+}
+
+// UFM replies the port numbers in strings, e.g. "1".
+fn port_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Number(u32),
+        String(String),
+    }
+
+    match Number::deserialize(deserializer)? {
+        Number::Number(n) => Ok(n),
+        Number::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
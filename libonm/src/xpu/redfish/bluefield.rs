@@ -1,6 +1,6 @@
 use super::{BMCVersion, Redfish, RedfishError, BMC};
 
-use crate::rest::{RestClient, RestConfig};
+use crate::rest::{RestAuth, RestClient, RestConfig, RestPolicy};
 use async_trait::async_trait;
 
 pub struct Bluefield {
@@ -37,12 +37,18 @@ impl Redfish for Bluefield {
     }
 
     async fn discover(&self) -> Result<(), RedfishError> {
-        if self.bmc_version().await.is_ok() {
-            return Ok(());
+        match self.bmc_version().await {
+            Ok(_) => return Ok(()),
+            Err(RedfishError::AuthFailure(_)) => {}
+            Err(e) => return Err(e),
         }
 
-        // Try to change the default password.
-        let default_bmc = Bluefield::default_bmc(&self.bmc.username, &self.bmc.address);
+        // The password is rejected, try to change the default password.
+        let default_bmc = BMC {
+            policy: self.bmc.policy.clone(),
+            fixture: self.bmc.fixture.clone(),
+            ..Bluefield::default_bmc(&self.bmc.username, &self.bmc.address)
+        };
         let default_redfish = Box::new(Bluefield::new(&default_bmc)?);
         default_redfish
             .change_password(self.bmc.password.clone())
@@ -59,8 +65,14 @@ impl Bluefield {
     pub fn new(bmc: &BMC) -> Result<Bluefield, RedfishError> {
         let config = RestConfig {
             address: bmc.address.clone(),
-            password: bmc.password.clone(),
-            username: bmc.username.clone(),
+            base_path: String::new(),
+            auth: RestAuth::Basic {
+                username: bmc.username.clone(),
+                password: bmc.password.clone(),
+            },
+            cert: None,
+            policy: bmc.policy.clone(),
+            fixture: bmc.fixture.clone(),
         };
 
         Ok(Bluefield {
@@ -74,6 +86,9 @@ impl Bluefield {
             address: addr.to_string(),
             password: DEFAULT_PASSWORD.to_string(),
             username: DEFAULT_USER.to_string(),
+            credential: None,
+            policy: RestPolicy::default(),
+            fixture: None,
         }
     }
 }
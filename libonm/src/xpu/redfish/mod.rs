@@ -23,11 +23,24 @@ pub enum RedfishError {
     IOError(String),
     #[error("{0}")]
     Json(String),
+    #[error("'{0}' not found")]
+    NotFound(String),
+    #[error("failed to auth '{0}'")]
+    AuthFailure(String),
+    /// The BMC replied an error status other than 401, 403 and 404, with the body of the response.
+    #[error("BMC failed with status {status}: {body}")]
+    Http { status: u16, body: String },
 }
 
 impl From<RestError> for RedfishError {
     fn from(value: RestError) -> Self {
-        RedfishError::RestError(value.to_string())
+        let reason = value.reason().unwrap_or_default();
+        match value {
+            RestError::NotFound { .. } => RedfishError::NotFound(reason),
+            RestError::AuthFailure { .. } => RedfishError::AuthFailure(reason),
+            RestError::Status { status, body, .. } => RedfishError::Http { status, body },
+            _ => RedfishError::RestError(value.to_string()),
+        }
     }
 }
 
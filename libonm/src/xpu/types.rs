@@ -1,6 +1,11 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::cred::{CredentialError, CredentialProvider};
+use crate::rest::{RestFixture, RestPolicy};
+
 use super::redfish::{self, Redfish, RedfishError};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,17 +25,63 @@ pub enum XPUError {
     InvalidConfig(String),
 }
 
+impl From<CredentialError> for XPUError {
+    fn from(value: CredentialError) -> Self {
+        XPUError::InvalidConfig(value.to_string())
+    }
+}
+
 impl From<RedfishError> for XPUError {
     fn from(value: RedfishError) -> Self {
         XPUError::Internal(value.to_string())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BMC {
+    /// The URL of BMC, e.g. `https://192.168.0.53`.
     pub address: String,
     pub username: String,
+    #[serde(default)]
     pub password: String,
+    /// The name of the credential of the password in the CredentialProvider instead of the
+    /// inline password; see BMC::resolve.
+    #[serde(default)]
+    pub credential: Option<String>,
+    /// The timeouts and retries of the requests to BMC.
+    #[serde(default)]
+    pub policy: RestPolicy,
+    /// Record the requests to BMC into a fixture file, or replay them from it.
+    #[serde(default)]
+    pub fixture: Option<RestFixture>,
+}
+
+impl BMC {
+    /// Replace the credential by its password from the provider.
+    pub async fn resolve(self, provider: &dyn CredentialProvider) -> Result<Self, XPUError> {
+        let Some(name) = &self.credential else {
+            return Ok(self);
+        };
+
+        Ok(BMC {
+            password: provider.get(name).await?,
+            credential: None,
+            ..self
+        })
+    }
+}
+
+impl fmt::Debug for BMC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BMC")
+            .field("address", &self.address)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("credential", &self.credential)
+            .field("policy", &self.policy)
+            .field("fixture", &self.fixture)
+            .finish()
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -68,6 +119,13 @@ impl ToString for XPUStatus {
 
 impl XPU {
     pub async fn new(bmc: &BMC) -> Result<Self, XPUError> {
+        if let Some(name) = &bmc.credential {
+            return Err(XPUError::InvalidConfig(format!(
+                "credential '{}' is not resolved",
+                name
+            )));
+        }
+
         let redfish = redfish::build(bmc)?;
         let bmc_ver = redfish.bmc_version().await?;
 
@@ -0,0 +1,52 @@
+// The helpers shared by the tests; not every test uses all of them.
+#![allow(dead_code)]
+
+use libonm::sm::mock::MockUfm;
+use libonm::sm::{
+    self, IbMtu, IbRate, Partition, PartitionKey, PartitionQoS, PortConfig, PortMembership, Ufm,
+};
+use libonm::Guid;
+
+pub fn guid(guid: &str) -> Guid {
+    guid.parse().unwrap()
+}
+
+pub fn pkey(pkey: &str) -> PartitionKey {
+    PartitionKey::try_from(pkey).unwrap()
+}
+
+pub fn port(g: &str, membership: PortMembership, index0: bool) -> PortConfig {
+    PortConfig {
+        guid: guid(g),
+        index0,
+        membership,
+    }
+}
+
+/// The partition "storage" with IPoIB.
+pub fn partition(key: &str, qos: Option<PartitionQoS>) -> Partition {
+    Partition {
+        name: "storage".to_string(),
+        pkey: pkey(key),
+        ipoib: true,
+        qos,
+    }
+}
+
+pub fn storage_qos() -> PartitionQoS {
+    PartitionQoS {
+        mtu_limit: IbMtu::Mtu4K,
+        service_level: 1,
+        rate_limit: IbRate::Rate100,
+    }
+}
+
+/// Start the mock UFM with a port of every `(GUID, host)` in `ports`, and connect to it.
+pub async fn start(ports: &[(&str, &str)]) -> (MockUfm, Ufm) {
+    let mock = MockUfm::start().await.unwrap();
+    for (g, host) in ports {
+        mock.add_port_with(guid(g), host);
+    }
+    let ufm = sm::connect(mock.config()).unwrap();
+    (mock, ufm)
+}
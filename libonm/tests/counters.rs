@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use libonm::sm::mock::MockUfm;
+use libonm::sm::{PortCounters, PortRates, UFMError};
+use libonm::Guid;
+
+mod common;
+use common::{guid, start};
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+const VGUID: &str = "0011223344560201";
+const HOSTS: &[(&str, &str)] = &[(GUID_1, "node1"), (GUID_2, "node2")];
+
+fn counters(g: &str, xmit_data: u64) -> PortCounters {
+    PortCounters {
+        guid: guid(g),
+        symbol_errors: 1,
+        xmit_data,
+        rcv_data: xmit_data * 2,
+        xmit_packets: 100,
+        xmit_wait: 7,
+        ..PortCounters::default()
+    }
+}
+
+/// The counters of both ports.
+fn add_counters(mock: &MockUfm) {
+    mock.set_port_counters(counters(GUID_1, 1000));
+    mock.set_port_counters(counters(GUID_2, 2000));
+}
+
+#[tokio::test]
+async fn port_counters() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_counters(&mock);
+
+    let c = ufm.port_counters(guid(GUID_1)).await.unwrap();
+    assert_eq!(c.name, "node1_1");
+    assert_eq!(c.system_name, "node1");
+    assert_eq!(
+        (c.active_speed.as_str(), c.active_width.as_str()),
+        ("HDR", "4x")
+    );
+    assert_eq!((c.symbol_errors, c.link_downed), (1, 0));
+    assert_eq!((c.xmit_data, c.rcv_data), (1000, 2000));
+    assert_eq!((c.xmit_packets, c.xmit_wait), (100, 7));
+    assert!(mock
+        .requests()
+        .contains(&"POST /monitoring/snapshot".to_string()));
+
+    // The virtual ports and the unknown ports are not monitored.
+    for g in [VGUID, "00000000000000ff"] {
+        let err = ufm.port_counters(guid(g)).await.unwrap_err();
+        assert!(matches!(err, UFMError::NotFound(_)), "{:?}", err);
+    }
+}
+
+#[tokio::test]
+async fn list_port_counters() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_counters(&mock);
+
+    let all = ufm
+        .list_port_counters(&[guid(GUID_2), guid(VGUID), guid(GUID_1)])
+        .await
+        .unwrap();
+    let guids: Vec<Guid> = all.iter().map(|c| c.guid).collect();
+    assert_eq!(guids, vec![guid(GUID_2), guid(GUID_1)]);
+    assert_eq!(all[0].xmit_data, 2000);
+
+    assert!(ufm.list_port_counters(&[]).await.unwrap().is_empty());
+}
+
+#[test]
+fn rates_between_samples() {
+    let earlier = counters(GUID_1, 1000);
+    let later = PortCounters {
+        symbol_errors: 3,
+        xmit_data: 3000,
+        rcv_data: 2500,
+        xmit_packets: 300,
+        // The counter was reset between the samples.
+        xmit_wait: 4,
+        ..earlier.clone()
+    };
+
+    let rates = PortRates::between(&earlier, &later, Duration::from_secs(2));
+    assert_eq!(rates.guid, guid(GUID_1));
+    assert_eq!(rates.symbol_errors, 2);
+    assert_eq!(rates.xmit_bytes, 4000.0);
+    assert_eq!(rates.rcv_bytes, 1000.0);
+    assert_eq!(rates.xmit_packets, 100.0);
+    assert_eq!(rates.xmit_wait, 2.0);
+
+    let rates = PortRates::between(&earlier, &later, Duration::ZERO);
+    assert_eq!(rates.xmit_bytes, 0.0);
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use libonm::cred::{self, CredentialConfig, CredentialError};
+use libonm::sm::mock::{MOCK_PASSWORD, MOCK_VERSION};
+use libonm::sm::{self, UFMConfig, UFMError};
+
+mod common;
+use common::start;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("onm-cred-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_secret(dir: &Path, name: &str, secret: &str, mode: u32) {
+    let path = dir.join(name);
+    fs::write(&path, secret).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn parse_provider() {
+    assert!(matches!(
+        "env".parse::<CredentialConfig>().unwrap(),
+        CredentialConfig::Env { prefix } if prefix.is_empty()
+    ));
+    assert!(matches!(
+        "env:ONM_".parse::<CredentialConfig>().unwrap(),
+        CredentialConfig::Env { prefix } if prefix == "ONM_"
+    ));
+    assert!(matches!(
+        "file:/etc/onm".parse::<CredentialConfig>().unwrap(),
+        CredentialConfig::File { dir } if dir == "/etc/onm"
+    ));
+    assert!(matches!(
+        "command:pass show onm/$1".parse::<CredentialConfig>().unwrap(),
+        CredentialConfig::Command { password_command } if password_command == "pass show onm/$1"
+    ));
+    assert!(matches!(
+        "keyring".parse::<CredentialConfig>().unwrap(),
+        CredentialConfig::Keyring
+    ));
+    assert!("file".parse::<CredentialConfig>().is_err());
+    assert!("vault:onm".parse::<CredentialConfig>().is_err());
+}
+
+#[tokio::test]
+async fn env_provider() {
+    std::env::set_var("ONM_TEST_UFM_ADMIN", "secret");
+    let provider = cred::build("env:ONM_TEST_".parse().unwrap());
+
+    assert_eq!(provider.get("ufm-admin").await.unwrap(), "secret");
+    assert!(matches!(
+        provider.get("bmc-admin").await,
+        Err(CredentialError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn file_provider() {
+    let dir = temp_dir("file");
+    write_secret(&dir, "ufm", "secret \n", 0o600);
+    write_secret(&dir, "bmc", "secret\n", 0o644);
+    let provider = cred::build(CredentialConfig::File {
+        dir: dir.display().to_string(),
+    });
+
+    assert_eq!(provider.get("ufm").await.unwrap(), "secret ");
+    assert!(matches!(
+        provider.get("bmc").await,
+        Err(CredentialError::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        provider.get("missing").await,
+        Err(CredentialError::NotFound(_))
+    ));
+    assert!(matches!(
+        provider.get("../ufm").await,
+        Err(CredentialError::InvalidCredential(_))
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn command_provider() {
+    let provider = cred::build(CredentialConfig::Command {
+        password_command: r#"[ "$1" = ufm ] && echo "secret-$1""#.to_string(),
+    });
+
+    assert_eq!(provider.get("ufm").await.unwrap(), "secret-ufm");
+    assert!(matches!(
+        provider.get("bmc").await,
+        Err(CredentialError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn resolve_ufm_credential() {
+    let (mock, _) = start(&[]).await;
+    let dir = temp_dir("ufm");
+    write_secret(&dir, "ufm", MOCK_PASSWORD, 0o600);
+    let provider = cred::build(CredentialConfig::File {
+        dir: dir.display().to_string(),
+    });
+
+    let conf = UFMConfig {
+        password: None,
+        credential: Some("ufm".to_string()),
+        ..mock.config()
+    };
+    let res = sm::connect(UFMConfig {
+        credential: Some("ufm".to_string()),
+        ..mock.config()
+    });
+    assert!(matches!(res, Err(UFMError::InvalidConfig(_))));
+
+    let ufm = sm::connect(conf.resolve(provider.as_ref()).await.unwrap()).unwrap();
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,172 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::StreamExt;
+use http::Method;
+
+use libonm::sm::mock::MockUfm;
+use libonm::sm::{Alarm, Event, EventFilter, Severity, UFMError};
+
+mod common;
+use common::start;
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+
+fn event(id: u64, severity: Severity, guid: &str, timestamp: &str) -> Event {
+    Event {
+        id,
+        name: "Link is down".to_string(),
+        description: format!("Peer port of {} is down", guid),
+        severity,
+        timestamp: timestamp.to_string(),
+        object_type: "Port".to_string(),
+        object_name: format!("{}_1", guid),
+        object_path: "default / Computer: node1 / HCA-1 / 1".to_string(),
+    }
+}
+
+/// The events of both ports, which are not in the order of their IDs.
+fn add_events(mock: &MockUfm) {
+    mock.add_event(event(3, Severity::Critical, GUID_1, "2024-05-10 09:30:00"));
+    mock.add_event(event(1, Severity::Info, GUID_1, "2024-05-10 09:10:00"));
+    mock.add_event(event(
+        2,
+        Severity::Warning,
+        GUID_2,
+        "2024-05-10T09:20:00.123",
+    ));
+}
+
+fn ids(events: &[Event]) -> Vec<u64> {
+    events.iter().map(|e| e.id).collect()
+}
+
+// 2024-05-10 09:15:00 UTC
+fn at_0915() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1715332500)
+}
+
+#[tokio::test]
+async fn list_events() {
+    let (mock, ufm) = start(&[]).await;
+    add_events(&mock);
+
+    let all = ufm.list_events(&EventFilter::default()).await.unwrap();
+    assert_eq!(ids(&all), vec![1, 2, 3]);
+
+    let filter = EventFilter {
+        severity: Some(Severity::Warning),
+        ..EventFilter::default()
+    };
+    assert_eq!(ids(&ufm.list_events(&filter).await.unwrap()), vec![2, 3]);
+
+    let filter = EventFilter {
+        guid: Some(GUID_1.parse().unwrap()),
+        ..EventFilter::default()
+    };
+    assert_eq!(ids(&ufm.list_events(&filter).await.unwrap()), vec![1, 3]);
+
+    let filter = EventFilter {
+        since: Some(at_0915()),
+        until: Some(at_0915() + Duration::from_secs(600)),
+        ..EventFilter::default()
+    };
+    assert_eq!(ids(&ufm.list_events(&filter).await.unwrap()), vec![2]);
+}
+
+#[tokio::test]
+async fn out_of_range_timestamps() {
+    let (mock, ufm) = start(&[]).await;
+    add_events(&mock);
+    for (id, timestamp) in [
+        (4, "9223372036854775807-01-01 00:00:00"),
+        (5, "1000000000000-01-01 00:00:00"),
+        (6, "500000000000-01-01 00:00:00"),
+    ] {
+        mock.add_event(event(id, Severity::Critical, GUID_1, timestamp));
+    }
+
+    // The timestamps out of the range of SystemTime are not matched by the time filters.
+    let filter = EventFilter {
+        since: Some(at_0915()),
+        ..EventFilter::default()
+    };
+    assert_eq!(ids(&ufm.list_events(&filter).await.unwrap()), vec![2, 3]);
+}
+
+#[tokio::test]
+async fn list_alarms() {
+    let (mock, ufm) = start(&[]).await;
+    add_events(&mock);
+    let alarm: Alarm = serde_json::from_value(serde_json::json!({
+        "id": 7,
+        "name": "Link is down",
+        "severity": "Critical",
+        "timestamp": "2024-05-10 09:30:00",
+        "type": "Port",
+        "object_name": format!("{}_1", GUID_1),
+        "reason": "Peer port is down",
+        "counter": 2,
+    }))
+    .unwrap();
+    mock.add_alarm(alarm.clone());
+
+    assert_eq!(
+        ufm.list_alarms(&EventFilter::default()).await.unwrap(),
+        vec![alarm]
+    );
+
+    let filter = EventFilter {
+        guid: Some(GUID_2.parse().unwrap()),
+        ..EventFilter::default()
+    };
+    assert!(ufm.list_alarms(&filter).await.unwrap().is_empty());
+
+    // The severity which is not known to libonm is the least.
+    let alarm: Alarm =
+        serde_json::from_value(serde_json::json!({ "id": 8, "severity": "Fatal" })).unwrap();
+    assert_eq!(alarm.severity, Severity::Unknown);
+    assert!(Severity::Unknown < Severity::Info);
+    assert!("major".parse::<Severity>().is_err());
+}
+
+#[tokio::test]
+async fn watch_events() {
+    let (mock, ufm) = start(&[]).await;
+    add_events(&mock);
+    let filter = EventFilter {
+        severity: Some(Severity::Warning),
+        ..EventFilter::default()
+    };
+    let events = ufm.watch_events(filter, Duration::from_millis(20));
+    futures_util::pin_mut!(events);
+
+    // The new events only, which match the filter; the current ones are skipped even after
+    // a few polls.
+    let first = events.next();
+    futures_util::pin_mut!(first);
+    assert!(tokio::time::timeout(Duration::from_millis(100), &mut first)
+        .await
+        .is_err());
+    let polls = mock
+        .requests()
+        .iter()
+        .filter(|r| *r == "GET /app/events")
+        .count();
+    assert!(polls > 1, "{}", polls);
+    mock.add_event(event(4, Severity::Info, GUID_1, "2024-05-10 09:40:00"));
+    mock.add_event(event(5, Severity::Critical, GUID_2, "2024-05-10 09:50:00"));
+    assert_eq!(first.await.unwrap().unwrap().id, 5);
+
+    // A failed poll is an error of the stream, which goes on at the next poll.
+    mock.inject_error(Method::GET, "/app/events", 500);
+    let err = events.next().await.unwrap().unwrap_err();
+    assert!(
+        matches!(err, UFMError::Http { status: 500, .. }),
+        "{:?}",
+        err
+    );
+
+    mock.add_event(event(6, Severity::Minor, GUID_1, "2024-05-10 10:00:00"));
+    assert_eq!(events.next().await.unwrap().unwrap().id, 6);
+}
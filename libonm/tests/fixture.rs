@@ -0,0 +1,169 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use libonm::sm::mock::{MOCK_PASSWORD, MOCK_VERSION};
+use libonm::sm::{self, PartitionQoS, PortMembership, SubnetManager, UFMConfig, UFMError};
+use libonm::xpu::{BMC, XPU};
+use libonm::{RestFixture, RestPolicy};
+
+mod common;
+use common::{partition, port, start, storage_qos};
+
+const GUID: &str = "1070fd0300176625";
+// Nothing listens on the address, so the requests must be replied by the fixtures.
+const OFFLINE_ADDRESS: &str = "https://127.0.0.1:1";
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn replay(file: &str) -> UFMConfig {
+    UFMConfig {
+        address: OFFLINE_ADDRESS.to_string(),
+        username: Some("admin".to_string()),
+        password: Some("unused".to_string()),
+        token: None,
+        cert: None,
+        credential: None,
+        policy: RestPolicy::default(),
+        fixture: Some(RestFixture::Replay(file.to_string())),
+        wait: None,
+    }
+}
+
+// The QoS of the partitions in `fixtures/ufm.json`.
+fn qos() -> Option<PartitionQoS> {
+    Some(PartitionQoS {
+        service_level: 0,
+        ..storage_qos()
+    })
+}
+
+/// Run the session captured in `fixtures/ufm.json`.
+async fn session(ufm: &dyn SubnetManager) {
+    let p = partition("0x5", qos());
+
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    ufm.create_partition(p.clone(), vec![port(GUID, PortMembership::Full, true)])
+        .await
+        .unwrap();
+    assert_eq!(ufm.list_partition().await.unwrap().len(), 2);
+    assert_eq!(ufm.get_partition("0x5").await.unwrap().qos, p.qos);
+
+    let ports = ufm.list_port(p.pkey).await.unwrap();
+    assert_eq!(ports.len(), 1);
+    assert_eq!(ports[0].system_name, "node1");
+
+    assert!(matches!(
+        ufm.get_partition("0x9").await,
+        Err(UFMError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn replay_ufm_fixture() {
+    let ufm = sm::build(sm::SMConfig::Ufm(replay(&fixture("ufm.json")))).unwrap();
+    session(ufm.as_ref()).await;
+
+    // All the responses are replied.
+    assert!(ufm.version().await.is_err());
+}
+
+#[tokio::test]
+async fn record_ufm_session() {
+    let file = std::env::temp_dir().join(format!("onm-fixture-{}.json", std::process::id()));
+    let file = file.display().to_string();
+    let _ = fs::remove_file(&file);
+
+    {
+        let (mock, _) = start(&[(GUID, "node1")]).await;
+
+        let ufm = sm::connect(UFMConfig {
+            fixture: Some(RestFixture::Record(file.clone())),
+            ..mock.config()
+        })
+        .unwrap();
+        session(&ufm).await;
+
+        let data = fs::read_to_string(&file).unwrap();
+        assert!(!data.contains(MOCK_PASSWORD));
+        assert!(!data.contains(&mock.address()));
+    }
+
+    // The mock is stopped, and the session is replayed from the fixture.
+    let ufm = sm::connect(replay(&file)).unwrap();
+    session(&ufm).await;
+
+    // The request is not the recorded one.
+    let ufm = sm::connect(replay(&file)).unwrap();
+    ufm.version().await.unwrap();
+    assert!(ufm
+        .create_partition(
+            partition("0x6", qos()),
+            vec![port(GUID, PortMembership::Full, true)]
+        )
+        .await
+        .is_err());
+
+    fs::remove_file(&file).unwrap();
+}
+
+#[tokio::test]
+async fn replay_bluefield_fixture() {
+    let bmc = BMC {
+        address: OFFLINE_ADDRESS.to_string(),
+        username: "root".to_string(),
+        password: "unused".to_string(),
+        credential: None,
+        policy: RestPolicy::default(),
+        fixture: Some(RestFixture::Replay(fixture("bluefield.json"))),
+    };
+
+    let xpu = XPU::new(&bmc).await.unwrap();
+    assert_eq!(xpu.bmc_version, "BF-23.10-4");
+}
+
+#[tokio::test]
+async fn record_non_json_body() {
+    // A server which replies the secret in a body which is not JSON, e.g. a page of a proxy.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request).unwrap();
+
+        let body = "<html>session token s3cr3t expired</html>";
+        let resp = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(resp.as_bytes()).unwrap();
+    });
+
+    let file = std::env::temp_dir().join(format!("onm-fixture-text-{}.json", std::process::id()));
+    let file = file.display().to_string();
+    let _ = fs::remove_file(&file);
+
+    let ufm = sm::connect(UFMConfig {
+        address,
+        policy: RestPolicy {
+            insecure_http: true,
+            ..RestPolicy::default()
+        },
+        fixture: Some(RestFixture::Record(file.clone())),
+        ..replay(&file)
+    })
+    .unwrap();
+    assert!(ufm.version().await.is_err());
+    server.join().unwrap();
+
+    let data = fs::read_to_string(&file).unwrap();
+    assert!(!data.contains("s3cr3t"), "{}", data);
+    assert!(data.contains("<redacted> (41 bytes)"), "{}", data);
+
+    fs::remove_file(&file).unwrap();
+}
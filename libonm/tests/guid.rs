@@ -0,0 +1,59 @@
+use libonm::sm::{PortConfig, PortMembership};
+use libonm::Guid;
+
+const GUID: &str = "1070fd0300176625";
+
+#[test]
+fn parse_guid_forms() {
+    let guid: Guid = GUID.parse().unwrap();
+    assert_eq!(guid.as_u64(), 0x1070fd0300176625);
+
+    for form in [
+        "0x1070fd0300176625",
+        "0X1070FD0300176625",
+        "1070:fd03:0017:6625",
+        " 1070FD0300176625\n",
+    ] {
+        assert_eq!(form.parse::<Guid>().unwrap(), guid, "{}", form);
+    }
+
+    for form in [
+        "",
+        "0x",
+        "1070::0017:6625",
+        "1070fd03001766250",
+        "1070fd03g0176625",
+        "1:2",
+        "abc:def",
+        "5",
+        "0x5",
+        "70fd0300176625",
+        "10:70:fd:03:00:17:66:25",
+        "1070:fd03:0017:66250",
+        "0x1070:fd03:0017:6625",
+    ] {
+        assert!(form.parse::<Guid>().is_err(), "{}", form);
+    }
+}
+
+#[test]
+fn display_guid() {
+    let guid = Guid::from(0x2c90300a1b2c3);
+
+    assert_eq!(guid.to_string(), "0002c90300a1b2c3");
+    assert_eq!(format!("{:#}", guid), "0x0002c90300a1b2c3");
+    assert_eq!(format!("{:<20}|", guid), "0002c90300a1b2c3    |");
+}
+
+#[test]
+fn serde_guid() {
+    let port: PortConfig =
+        serde_json::from_str(r#"{"guid":"1070:FD03:0017:6625","membership":"limited"}"#).unwrap();
+    assert_eq!(port.guid, GUID.parse().unwrap());
+    assert_eq!(port.membership, PortMembership::Limited);
+
+    let json = serde_json::to_value(&port).unwrap();
+    assert_eq!(json["guid"], GUID);
+
+    assert!(serde_json::from_str::<PortConfig>(r#"{"guid":"node1"}"#).is_err());
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use libonm::sm::{self, JobStatus, UFMConfig, UFMError};
+
+mod common;
+use common::{partition, pkey, start};
+
+fn job(id: &str, status: &str, summary: &str) -> JobStatus {
+    JobStatus {
+        id: id.to_string(),
+        status: status.to_string(),
+        summary: summary.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn wait_for_jobs_of_changes() {
+    let (mock, ufm) = start(&[]).await;
+    mock.run_jobs(2);
+
+    // The changes are not waited for by default.
+    ufm.create_partition(partition("0x5", None), vec![])
+        .await
+        .unwrap();
+    assert!(!mock.requests().iter().any(|r| r.starts_with("GET /jobs")));
+
+    let ufm = sm::connect(UFMConfig {
+        wait: Some(Duration::from_secs(10)),
+        ..mock.config()
+    })
+    .unwrap();
+    ufm.delete_partition("0x5").await.unwrap();
+    let polls = mock
+        .requests()
+        .iter()
+        .filter(|r| *r == "GET /jobs/2")
+        .count();
+    assert_eq!(polls, 2);
+    assert!(mock.partition(pkey("0x5")).is_none());
+}
+
+#[tokio::test]
+async fn job_progress() {
+    let (mock, ufm) = start(&[]).await;
+    mock.run_jobs(4);
+    ufm.create_partition(partition("0x5", None), vec![])
+        .await
+        .unwrap();
+
+    let job = ufm.job("1").with_interval(Duration::from_millis(10));
+    assert_eq!(job.id(), "1");
+
+    let mut progress = vec![];
+    let status = job.wait_with(|s| progress.push(s.progress)).await.unwrap();
+    assert_eq!(progress, vec![25, 50, 75, 100]);
+    assert!(status.is_completed());
+    assert_eq!(status.description, "add pkey");
+}
+
+#[tokio::test]
+async fn failed_jobs() {
+    let (mock, ufm) = start(&[]).await;
+    mock.add_job(job("10", "Failed", "pkey 0x5 is in use"));
+    mock.add_job(job("20", "Completed With Errors", ""));
+    mock.add_job(JobStatus {
+        parent_id: Some("20".to_string()),
+        ..job("21", "Completed", "")
+    });
+    mock.add_job(JobStatus {
+        parent_id: Some("20".to_string()),
+        ..job("22", "Failed", "switch sw1 is unreachable")
+    });
+
+    let err = ufm.job("10").wait().await.unwrap_err();
+    assert!(
+        matches!(&err, UFMError::JobFailed { id, reason } if id == "10" && reason == "pkey 0x5 is in use"),
+        "{:?}",
+        err
+    );
+
+    // The reason is from the failed sub-jobs if the job has no summary.
+    let subs = ufm.job("20").sub_jobs().await.unwrap();
+    assert_eq!(subs.len(), 2);
+    let err = ufm.job("20").wait().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "job 20 failed: sub-job 22: switch sw1 is unreachable"
+    );
+
+    let err = ufm.job("404").wait().await.unwrap_err();
+    assert!(matches!(err, UFMError::NotFound(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn job_timeout() {
+    let (mock, ufm) = start(&[]).await;
+    mock.add_job(JobStatus {
+        progress: 40,
+        ..job("10", "Running", "")
+    });
+
+    let job = ufm.job("10");
+    assert!(!job.status().await.unwrap().is_done());
+
+    let err = job
+        .with_timeout(Duration::from_millis(50))
+        .with_interval(Duration::from_millis(10))
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, UFMError::JobTimeout { id, progress: 40 } if id == "10"),
+        "{:?}",
+        err
+    );
+}
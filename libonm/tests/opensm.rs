@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use libonm::sm::{
+    IbMtu, IbRate, OpenSM, OpenSMConfig, Partition, PartitionQoS, PortMembership, SubnetManager,
+    UFMError,
+};
+
+mod common;
+use common::{guid, pkey, port};
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+const GUID_3: &str = "0011223344560201";
+
+// The partitions.conf in the form written by OpenSM, so it's rewritten as is.
+const PARTITIONS: &str = "\
+# The default partition.
+Default=0x7fff, ipoib :
+    ALL=full;
+
+# Storage, managed by hand.
+storage=0x0005, ipoib, mtu=5, rate=7, sl=0, mgid=ff12:401b::1, scope=2 :
+    0x1070fd0300176625=full,
+    0x0011223344560200=limited;
+# The end of the partitions.
+";
+
+fn temp_conf(name: &str, conf: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("onm-opensm-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("partitions.conf");
+    fs::write(&path, conf).unwrap();
+    path
+}
+
+fn opensm(path: &Path, reread_command: Option<&str>) -> OpenSM {
+    OpenSM::new(OpenSMConfig {
+        partitions: path.to_string_lossy().to_string(),
+        reread_command: reread_command.map(str::to_string),
+    })
+}
+
+// The partition without a name, so OpenSM names it by its pkey.
+fn partition(key: &str, ipoib: bool) -> Partition {
+    Partition {
+        name: String::new(),
+        ipoib,
+        ..common::partition(key, None)
+    }
+}
+
+#[tokio::test]
+async fn round_trip() {
+    let path = temp_conf("round-trip", PARTITIONS);
+    let sm = opensm(&path, None);
+
+    let parts = sm.list_partition().await.unwrap();
+    assert_eq!(parts.len(), 2);
+    assert!(parts[0].pkey.is_default());
+    assert_eq!(parts[1].name, "storage");
+    assert!(parts[1].ipoib);
+    assert_eq!(
+        parts[1].qos,
+        Some(PartitionQoS {
+            mtu_limit: IbMtu::Mtu4K,
+            service_level: 0,
+            rate_limit: IbRate::Rate40,
+        })
+    );
+
+    let ports = sm.list_port(pkey("0x5")).await.unwrap();
+    assert_eq!(ports.len(), 2);
+    assert_eq!(ports[0].guid, guid(GUID_1));
+    assert_eq!(ports[1].membership, Some(PortMembership::Limited));
+
+    // The same QoS is written in place, so the file is rewritten as is.
+    sm.update_partition_qos(parts[1].clone()).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), PARTITIONS);
+}
+
+#[tokio::test]
+async fn partition_lifecycle() {
+    let path = temp_conf("lifecycle", PARTITIONS);
+    let sm = opensm(&path, None);
+
+    sm.create_partition(
+        partition("0x6", true),
+        vec![port(GUID_1, PortMembership::Full, true)],
+    )
+    .await
+    .unwrap();
+    let p = sm.get_partition("0x6").await.unwrap();
+    assert_eq!(p.name, "pkey_0x0006");
+    assert!(p.ipoib);
+    let err = sm
+        .create_partition(partition("0x6", true), vec![])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UFMError::InvalidConfig(_)), "{:?}", err);
+
+    let results = sm
+        .bind_ports(
+            partition("0x6", true),
+            vec![
+                port(GUID_2, PortMembership::Limited, true),
+                port(GUID_3, PortMembership::Full, false),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    // OpenSM sets index0 per partition, so the ports without index0 are failed.
+    assert!(!results[1].is_ok());
+    assert_eq!(sm.list_port(pkey("0x6")).await.unwrap().len(), 2);
+
+    sm.unbind_ports(pkey("0x6"), vec![guid(GUID_1)])
+        .await
+        .unwrap();
+    let ports = sm.list_port(pkey("0x6")).await.unwrap();
+    assert_eq!(ports.len(), 1);
+    assert_eq!(ports[0].guid, guid(GUID_2));
+    assert_eq!(ports[0].index0, Some(true));
+
+    sm.delete_partition("0x6").await.unwrap();
+    let err = sm.get_partition("0x6").await.unwrap_err();
+    assert!(matches!(err, UFMError::NotFound(_)), "{:?}", err);
+    let err = sm.delete_partition("0x7fff").await.unwrap_err();
+    assert!(matches!(err, UFMError::InvalidConfig(_)), "{:?}", err);
+
+    // The comments, the unknown flags and the default partition are kept.
+    assert_eq!(fs::read_to_string(&path).unwrap(), PARTITIONS);
+}
+
+#[tokio::test]
+async fn bind_keeps_ipoib() {
+    let path = temp_conf("ipoib", PARTITIONS);
+    let sm = opensm(&path, None);
+
+    sm.bind_ports(
+        partition("0x5", false),
+        vec![port(GUID_3, PortMembership::Full, false)],
+    )
+    .await
+    .unwrap();
+    assert!(sm.get_partition("0x5").await.unwrap().ipoib);
+
+    sm.update_partition_ipoib(partition("0x5", false), vec![])
+        .await
+        .unwrap();
+    assert!(!sm.get_partition("0x5").await.unwrap().ipoib);
+    let conf = fs::read_to_string(&path).unwrap();
+    assert!(conf.contains("storage=0x0005, mtu=5, rate=7, sl=0, mgid=ff12:401b::1, scope=2 :"));
+    assert!(conf.contains("0x0011223344560201=full;"));
+
+    // A new partition is created with its ipoib.
+    sm.bind_ports(
+        partition("0x6", false),
+        vec![port(GUID_1, PortMembership::Full, false)],
+    )
+    .await
+    .unwrap();
+    assert!(!sm.get_partition("0x6").await.unwrap().ipoib);
+}
+
+#[tokio::test]
+async fn reread_command() {
+    let path = temp_conf("reread", PARTITIONS);
+    let reread = path.with_extension("reread");
+    let sm = opensm(&path, Some(&format!("touch {}", reread.display())));
+
+    sm.delete_partition("0x5").await.unwrap();
+    assert!(reread.exists());
+    assert!(!path.with_extension("conf.tmp").exists());
+
+    let sm = opensm(&path, Some("echo not running >&2; false"));
+    let err = sm
+        .create_partition(partition("0x6", false), vec![])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not running"), "{}", err);
+    // The file is written before OpenSM is triggered.
+    assert!(sm.get_partition("0x6").await.is_ok());
+}
+
+#[tokio::test]
+async fn invalid_partitions() {
+    let path = temp_conf("invalid", "storage=0x0005 : 0x1070fd0300176625=full");
+    let err = opensm(&path, None).list_partition().await.unwrap_err();
+    assert!(matches!(err, UFMError::InvalidConfig(_)), "{:?}", err);
+
+    let path = temp_conf("membership", "storage=0x0005 : 0x1070fd0300176625=half;");
+    let err = opensm(&path, None).list_partition().await.unwrap_err();
+    assert!(matches!(err, UFMError::InvalidConfig(_)), "{:?}", err);
+
+    // A missing file has no partitions.
+    let sm = opensm(&path.with_file_name("missing.conf"), None);
+    assert!(sm.list_partition().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn inline_comments() {
+    let path = temp_conf(
+        "comments",
+        "storage=0x0005, ipoib : # The storage.\n    0x1070fd0300176625=full; # The end.\n",
+    );
+    let sm = opensm(&path, None);
+
+    // The comments inside the entry or after it are moved above it.
+    sm.unbind_ports(pkey("0x5"), vec![]).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# The storage.\n# The end.\nstorage=0x0005, ipoib :\n    0x1070fd0300176625=full;\n"
+    );
+}
+
+#[tokio::test]
+async fn concurrent_changes() {
+    let path = temp_conf("concurrent", PARTITIONS);
+
+    // Every change is kept, as the changes are serialized by the lock of the file.
+    let tasks: Vec<_> = (0x10..0x20)
+        .map(|key| {
+            let sm = opensm(&path, None);
+            tokio::spawn(async move {
+                sm.create_partition(partition(&format!("{:#x}", key), false), vec![])
+                    .await
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    let sm = opensm(&path, None);
+    assert_eq!(sm.list_partition().await.unwrap().len(), 2 + 16);
+    assert!(path.with_extension("conf.lock").exists());
+}
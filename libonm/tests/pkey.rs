@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+
+use libonm::sm::{PartitionKey, UFMError};
+
+#[test]
+fn parse_partition_key() {
+    for pkey in ["0x5", "0X0005", "5", " 0x5 "] {
+        let pkey: PartitionKey = pkey.parse().unwrap();
+        assert_eq!(pkey.base(), 5);
+        assert!(!pkey.is_full_member());
+    }
+    assert!("0x7fff".parse::<PartitionKey>().unwrap().is_default());
+
+    for pkey in ["0x0", "0", "0x8000", "0x10000", "-1", "pkey5", ""] {
+        assert!(
+            matches!(pkey.parse::<PartitionKey>(), Err(UFMError::InvalidPKey(_))),
+            "{}",
+            pkey
+        );
+    }
+
+    // The same validation whatever the type.
+    assert!(PartitionKey::try_from(0).is_err());
+    assert!(PartitionKey::try_from(0x10005).is_err());
+    assert!(PartitionKey::try_from(-5).is_err());
+    assert_eq!(PartitionKey::try_from(0x8005).unwrap().base(), 5);
+    assert!(PartitionKey::try_from(0x8005u16).unwrap().is_full_member());
+}
+
+#[test]
+fn membership_bit() {
+    let full: PartitionKey = "0x8005".parse().unwrap();
+    assert_eq!(full.base(), 5);
+    assert!(full.is_full_member());
+    assert_eq!(full.value(), 0x8005);
+
+    // The keys of the same partition are equal.
+    let limited = PartitionKey::try_from("0x5").unwrap();
+    assert_eq!(full, limited);
+    assert_eq!(HashSet::from([full, limited]).len(), 1);
+    assert_eq!(limited.with_full_member(true).value(), 0x8005);
+
+    assert_eq!(full.to_string(), "0x5");
+    assert_eq!(format!("{:#}", full), "0x8005");
+    assert_eq!(format!("{:#}", limited), "0x0005");
+}
+
+#[test]
+fn serde_partition_key() {
+    let pkey: PartitionKey = serde_json::from_str("32773").unwrap();
+    assert!(pkey.is_full_member());
+    assert_eq!(serde_json::to_string(&pkey).unwrap(), "32773");
+
+    let pkey: PartitionKey = serde_json::from_str(r#""0x5""#).unwrap();
+    assert_eq!(serde_json::to_string(&pkey).unwrap(), "5");
+
+    assert!(serde_json::from_str::<PartitionKey>("0").is_err());
+    assert!(serde_json::from_str::<PartitionKey>("65536").is_err());
+}
@@ -0,0 +1,80 @@
+use http::Method;
+
+use libonm::sm::{PortAction, PortId, UFMError, Ufm};
+
+mod common;
+use common::{guid, pkey, start};
+
+const GUID_1: &str = "1070fd0300176625";
+const HOSTS: &[(&str, &str)] = &[(GUID_1, "node1")];
+
+async fn logical_state(ufm: &Ufm) -> String {
+    let ports = ufm.list_port(pkey("0x7fff")).await.unwrap();
+    ports
+        .into_iter()
+        .find(|p| p.guid == guid(GUID_1))
+        .unwrap()
+        .logical_state
+}
+
+#[test]
+fn parse_port_id() {
+    assert_eq!(
+        GUID_1.parse::<PortId>().unwrap(),
+        PortId::Guid(guid(GUID_1))
+    );
+    assert_eq!(
+        "node1/1".parse::<PortId>().unwrap(),
+        PortId::Name {
+            system: "node1".to_string(),
+            port: 1
+        }
+    );
+    assert_eq!("node1/1".parse::<PortId>().unwrap().to_string(), "node1/1");
+
+    for port in ["node1", "node1/0", "/1", "node1/x"] {
+        assert!(port.parse::<PortId>().is_err(), "{}", port);
+    }
+}
+
+#[tokio::test]
+async fn disable_enable_reset() {
+    let (mock, ufm) = start(HOSTS).await;
+
+    ufm.disable_port(&PortId::Guid(guid(GUID_1))).await.unwrap();
+    assert_eq!(logical_state(&ufm).await, "Disabled");
+    let requests = mock.requests();
+    assert!(requests.contains(&"POST /actions".to_string()));
+    assert!(requests.contains(&"GET /jobs/1".to_string()));
+
+    // The name of the system is case-insensitive.
+    ufm.enable_port(&"NODE1/1".parse().unwrap()).await.unwrap();
+    assert_eq!(logical_state(&ufm).await, "Active");
+
+    ufm.reset_port(&PortId::Guid(guid(GUID_1))).await.unwrap();
+    assert!(mock.requests().contains(&"GET /jobs/3".to_string()));
+}
+
+#[tokio::test]
+async fn port_action_errors() {
+    let (mock, ufm) = start(HOSTS).await;
+
+    for port in ["0011223344560200", "node1/2", "node2/1"] {
+        let err = ufm
+            .port_action(&port.parse().unwrap(), PortAction::Disable)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UFMError::NotFound(_)), "{:?}", err);
+    }
+
+    mock.inject_error(Method::GET, "/jobs/1", 500);
+    let err = ufm
+        .disable_port(&PortId::Guid(guid(GUID_1)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, UFMError::Http { status: 500, .. }),
+        "{:?}",
+        err
+    );
+}
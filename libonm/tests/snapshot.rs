@@ -0,0 +1,146 @@
+use libonm::sm::mock::{MockUfm, MOCK_VERSION};
+use libonm::sm::{
+    Conflict, PartitionQoS, PartitionSnapshot, PortMembership, SubnetManager, UFMError, Ufm,
+    SNAPSHOT_VERSION,
+};
+
+mod common;
+use common::{guid, partition, pkey, port, start, storage_qos};
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+
+// The UFM with the partitions 0x5 of two members and 0x6 without members.
+async fn source() -> (MockUfm, Ufm) {
+    let (mock, ufm) = start(&[]).await;
+    ufm.create_partition(
+        partition("0x5", Some(storage_qos())),
+        vec![
+            port(GUID_1, PortMembership::Full, true),
+            port(GUID_2, PortMembership::Limited, false),
+        ],
+    )
+    .await
+    .unwrap();
+    ufm.create_partition(partition("0x6", Some(PartitionQoS::default())), vec![])
+        .await
+        .unwrap();
+
+    (mock, ufm)
+}
+
+#[tokio::test]
+async fn export_snapshot() {
+    let (_mock, ufm) = source().await;
+
+    let snapshot = ufm.export().await.unwrap();
+    assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+    assert_eq!(snapshot.source, MOCK_VERSION);
+    let pkeys: Vec<&str> = snapshot
+        .partitions
+        .iter()
+        .map(|p| p.pkey.as_str())
+        .collect();
+    assert_eq!(pkeys, vec!["0x5", "0x6"]);
+
+    let storage = &snapshot.partitions[0];
+    assert_eq!(storage.name, "storage");
+    assert_eq!(storage.qos, Some(storage_qos()));
+    let limited = storage
+        .members
+        .iter()
+        .find(|m| m.guid == guid(GUID_2))
+        .unwrap();
+    assert_eq!(limited.membership, PortMembership::Limited);
+    assert!(!limited.index0);
+
+    // The snapshot is the same in JSON and TOML.
+    let value = serde_json::to_value(&snapshot).unwrap();
+    let json = PartitionSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&json).unwrap(), value);
+    let toml = PartitionSnapshot::from_toml(&snapshot.to_toml().unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&toml).unwrap(), value);
+}
+
+#[tokio::test]
+async fn import_snapshot() {
+    let (_source, ufm) = source().await;
+    let snapshot = ufm.export().await.unwrap();
+
+    let (mock, ufm) = start(&[]).await;
+    let changes = ufm.import(&snapshot, Conflict::Fail).await.unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let storage = mock.partition(pkey("0x5")).unwrap();
+    assert_eq!(storage.name, "storage");
+    assert_eq!(storage.qos, storage_qos());
+    assert_eq!(storage.guids.len(), 2);
+    let limited = storage
+        .guids
+        .iter()
+        .find(|g| g.guid == guid(GUID_2))
+        .unwrap();
+    assert_eq!(limited.membership, PortMembership::Limited);
+    assert!(!limited.index0);
+    assert!(mock.partition(pkey("0x6")).is_some());
+
+    // The partitions are imported already.
+    let changes = ufm
+        .diff_snapshot(&snapshot, Conflict::Overwrite)
+        .await
+        .unwrap();
+    assert!(changes.is_empty(), "{:?}", changes);
+}
+
+#[tokio::test]
+async fn import_conflicts() {
+    let (_source, ufm) = source().await;
+    let snapshot = ufm.export().await.unwrap();
+
+    // The partition 0x5 exists with another QoS and member.
+    let (mock, ufm) = start(&[]).await;
+    ufm.create_partition(
+        partition("0x5", Some(PartitionQoS::default())),
+        vec![port(GUID_2, PortMembership::Full, true)],
+    )
+    .await
+    .unwrap();
+
+    let err = ufm.import(&snapshot, Conflict::Fail).await.unwrap_err();
+    assert!(matches!(err, UFMError::InvalidConfig(_)), "{:?}", err);
+    assert!(mock.partition(pkey("0x6")).is_none());
+
+    ufm.import(&snapshot, Conflict::Skip).await.unwrap();
+    assert!(mock.partition(pkey("0x6")).is_some());
+    let storage = mock.partition(pkey("0x5")).unwrap();
+    assert_eq!(storage.qos, PartitionQoS::default());
+    assert_eq!(storage.guids.len(), 1);
+
+    ufm.import(&snapshot, Conflict::Overwrite).await.unwrap();
+    let storage = mock.partition(pkey("0x5")).unwrap();
+    assert_eq!(storage.qos, storage_qos());
+    assert_eq!(storage.guids.len(), 2);
+    let limited = storage
+        .guids
+        .iter()
+        .find(|g| g.guid == guid(GUID_2))
+        .unwrap();
+    assert_eq!(limited.membership, PortMembership::Limited);
+}
+
+#[test]
+fn snapshot_version() {
+    let snapshot = PartitionSnapshot::from_toml("version = 1").unwrap();
+    assert!(snapshot.partitions.is_empty());
+
+    for s in ["version = 0", "version = 2", "source = \"6.11.1-2\""] {
+        assert!(PartitionSnapshot::from_toml(s).is_err(), "{}", s);
+    }
+    assert!(PartitionSnapshot::from_json(r#"{"version": 2, "partition": []}"#).is_err());
+
+    assert_eq!(
+        "Overwrite".parse::<Conflict>().unwrap(),
+        Conflict::Overwrite
+    );
+    assert!("replace".parse::<Conflict>().is_err());
+}
@@ -0,0 +1,131 @@
+use libonm::sm::mock::MockUfm;
+use libonm::sm::{Link, System, SystemType, Topology};
+
+mod common;
+use common::{guid, start};
+
+const HOST_1: &str = "1070fd0300176624";
+const HOST_1_PORT: &str = "1070fd0300176625";
+const HOST_2: &str = "0011223344560100";
+const LEAF_1: &str = "b8cef60300000001";
+const LEAF_2: &str = "b8cef60300000002";
+const SPINE_1: &str = "b8cef60300000011";
+const SPINE_2: &str = "b8cef60300000012";
+const HOSTS: &[(&str, &str)] = &[(HOST_1_PORT, "node1")];
+
+fn system(g: &str, name: &str, system_type: SystemType) -> System {
+    System {
+        guid: guid(g),
+        name: name.to_string(),
+        system_type,
+        model: String::new(),
+        ip: String::new(),
+    }
+}
+
+fn link(src: &str, src_port: u32, dst: &str, dst_port: u32) -> Link {
+    Link {
+        source_guid: guid(src),
+        source_port: src_port,
+        destination_guid: guid(dst),
+        destination_port: dst_port,
+        width: "IB_4x".to_string(),
+        speed: "HDR".to_string(),
+        state: "Active".to_string(),
+    }
+}
+
+/// Two hosts on their own leaf switches, and both leaves are linked to both spines.
+fn add_fabric(mock: &MockUfm) {
+    mock.add_system(system(HOST_1, "node1", SystemType::Host));
+    mock.add_system(system(HOST_2, "node2", SystemType::Host));
+    mock.add_system(system(LEAF_1, "leaf1", SystemType::Switch));
+    mock.add_system(system(LEAF_2, "leaf2", SystemType::Switch));
+    mock.add_system(system(SPINE_1, "spine1", SystemType::Switch));
+    mock.add_system(system(SPINE_2, "spine2", SystemType::Switch));
+
+    // The links of the hosts are by the port GUID or the system GUID.
+    mock.add_link(link(HOST_1_PORT, 1, LEAF_1, 3));
+    mock.add_link(link(LEAF_2, 4, HOST_2, 1));
+    for (leaf, port) in [(LEAF_1, 1), (LEAF_2, 2)] {
+        mock.add_link(link(leaf, 33, SPINE_1, port));
+        mock.add_link(link(SPINE_2, port, leaf, 34));
+    }
+}
+
+#[tokio::test]
+async fn list_systems_and_links() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_fabric(&mock);
+
+    assert_eq!(ufm.list_systems(None).await.unwrap().len(), 6);
+    let switches = ufm.list_systems(Some(SystemType::Switch)).await.unwrap();
+    assert_eq!(switches.len(), 4);
+    assert!(switches.iter().all(|s| s.system_type == SystemType::Switch));
+
+    assert_eq!(ufm.list_links().await.unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn uplinks_of_host() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_fabric(&mock);
+    let topology = ufm.topology().await.unwrap();
+
+    let tiers = topology.tiers();
+    assert_eq!(tiers[&guid(HOST_1)], 0);
+    assert_eq!(tiers[&guid(LEAF_1)], 1);
+    assert_eq!(tiers[&guid(SPINE_2)], 2);
+
+    // By the name, the system GUID or the port GUID.
+    for host in ["node1", HOST_1, HOST_1_PORT] {
+        assert_eq!(topology.find(host).unwrap().guid, guid(HOST_1));
+    }
+
+    let paths: Vec<String> = topology
+        .uplinks(guid(HOST_1_PORT))
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|hop| hop.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "node1/1 -> leaf1/3, leaf1/33 -> spine1/1",
+            "node1/1 -> leaf1/3, leaf1/34 -> spine2/1",
+        ]
+    );
+
+    let paths = topology.uplinks(guid(HOST_2));
+    assert_eq!(paths.len(), 2);
+    assert!(paths.iter().all(|path| path[0].to.name == "leaf2"));
+}
+
+#[tokio::test]
+async fn export_topology() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_fabric(&mock);
+    let topology = ufm.topology().await.unwrap();
+
+    let dot = topology.to_dot();
+    assert!(dot.starts_with("graph fabric {"));
+    assert!(dot.contains(&format!("\"{}\" [label=\"leaf1\", shape=box];", LEAF_1)));
+    // The links of the host ports are of the host.
+    assert!(dot.contains(&format!("\"{}\" -- \"{}\"", HOST_1, LEAF_1)));
+
+    let json = serde_json::to_string(&topology).unwrap();
+    let topology: Topology = serde_json::from_str(&json).unwrap();
+    assert_eq!(topology.uplinks(guid(HOST_1_PORT)).len(), 2);
+
+    // UFM replies the port numbers in strings.
+    let link: Link = serde_json::from_str(&format!(
+        r#"{{"source_guid":"{}","source_port":"3","destination_guid":"{}","destination_port":"1"}}"#,
+        LEAF_1, SPINE_1
+    ))
+    .unwrap();
+    assert_eq!(link.source_port, 3);
+}
@@ -0,0 +1,513 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use http::Method;
+
+use libonm::sm::mock::{MockUfm, MOCK_PASSWORD, MOCK_TOKEN, MOCK_VERSION};
+use libonm::sm::{
+    self, Change, DesiredState, IbMtu, IbRate, Partition, PartitionQoS, PortMembership, PortType,
+    SubnetManager, UFMConfig, UFMError, VirtualPort,
+};
+use libonm::RestPolicy;
+
+mod common;
+use common::{guid, partition, pkey, port, start, storage_qos};
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+const VGUID: &str = "0011223344560201";
+const HOSTS: &[(&str, &str)] = &[(GUID_1, "node1"), (GUID_2, "node2")];
+
+/// The virtual port of node2.
+fn add_vport(mock: &MockUfm) {
+    mock.add_vport(VirtualPort {
+        virtual_port_guid: guid(VGUID),
+        system_guid: "00112233445601ff".to_string(),
+        virtual_port_lid: 3,
+        system_name: "node2".to_string(),
+        virtual_port_state: "Active".to_string(),
+        port_guid: guid(GUID_2),
+    });
+}
+
+#[tokio::test]
+async fn version_and_configuration() {
+    let (_mock, ufm) = start(HOSTS).await;
+
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    assert_eq!(ufm.get_configuration().await.unwrap().qos, 0);
+}
+
+#[tokio::test]
+async fn token_auth() {
+    let (mock, _) = start(HOSTS).await;
+
+    let ufm = sm::connect(UFMConfig {
+        address: mock.address(),
+        username: None,
+        password: None,
+        token: Some(MOCK_TOKEN.to_string()),
+        cert: None,
+        ..mock.config()
+    })
+    .unwrap();
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+
+    let ufm = sm::connect(UFMConfig {
+        password: Some("wrong".to_string()),
+        ..mock.config()
+    })
+    .unwrap();
+    assert!(matches!(ufm.version().await, Err(UFMError::AuthFailure(_))));
+}
+
+#[tokio::test]
+async fn cert_auth() {
+    let (mock, _) = start(HOSTS).await;
+
+    let ufm = sm::connect(mock.cert_config().unwrap()).unwrap();
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    assert!(mock
+        .requests()
+        .contains(&"GET /app/ufm_version".to_string()));
+
+    // The prefix of the client certificate is unauthorized without the certificate.
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let resp = client
+        .get(format!("{}/ufmRestV2/app/ufm_version", mock.address()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn address_of_ufm() {
+    let (mock, _) = start(HOSTS).await;
+
+    for address in [
+        format!("{}/", mock.address()),
+        mock.address().trim_start_matches("https://").to_string(),
+    ] {
+        let ufm = sm::connect(UFMConfig {
+            address,
+            ..mock.config()
+        })
+        .unwrap();
+        assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    }
+
+    let res = sm::connect(UFMConfig {
+        address: mock.address().replace("https://", "ftp://"),
+        ..mock.config()
+    });
+    assert!(matches!(res, Err(UFMError::InvalidConfig(_))));
+
+    // The password is not sent over http unless it's allowed.
+    let address = mock.address().replace("https://", "http://");
+    let res = sm::connect(UFMConfig {
+        address: address.clone(),
+        ..mock.config()
+    });
+    assert!(matches!(res, Err(UFMError::InvalidConfig(_))));
+    sm::connect(UFMConfig {
+        address,
+        policy: RestPolicy {
+            insecure_http: true,
+            ..RestPolicy::default()
+        },
+        ..mock.config()
+    })
+    .unwrap();
+}
+
+#[tokio::test]
+async fn partition_lifecycle() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_vport(&mock);
+    let p = partition("0x5", Some(storage_qos()));
+
+    ufm.create_partition(
+        p.clone(),
+        vec![
+            port(GUID_1, PortMembership::Full, true),
+            port(VGUID, PortMembership::Limited, false),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let parts = ufm.list_partition().await.unwrap();
+    assert_eq!(parts.len(), 2);
+    assert!(parts.iter().any(|part| part.pkey.is_default()));
+
+    let part = ufm.get_partition("0x5").await.unwrap();
+    assert_eq!(part.name, "storage");
+    assert!(part.ipoib);
+    assert_eq!(part.qos, p.qos);
+
+    let ports = ufm.list_port(p.pkey).await.unwrap();
+    assert_eq!(ports.len(), 2);
+    let pf = ports.iter().find(|port| port.guid == guid(GUID_1)).unwrap();
+    assert_eq!(pf.system_name, "node1");
+    assert_eq!(pf.membership, Some(PortMembership::Full));
+    assert_eq!(pf.index0, Some(true));
+    let vf = ports.iter().find(|port| port.guid == guid(VGUID)).unwrap();
+    assert!(matches!(vf.port_type, Some(PortType::Virtual)));
+    assert_eq!(vf.parent_guid, Some(guid(GUID_2)));
+    assert_eq!(vf.membership, Some(PortMembership::Limited));
+    assert_eq!(vf.index0, Some(false));
+
+    ufm.update_partition_qos(Partition {
+        qos: Some(PartitionQoS::default()),
+        ..p.clone()
+    })
+    .await
+    .unwrap();
+    assert_eq!(mock.partition(p.pkey).unwrap().qos, PartitionQoS::default());
+
+    ufm.unbind_ports(p.pkey, vec![guid(VGUID)]).await.unwrap();
+    assert_eq!(mock.partition(p.pkey).unwrap().guids.len(), 1);
+
+    ufm.delete_partition("0x5").await.unwrap();
+    assert!(mock.partition(p.pkey).is_none());
+    assert!(ufm.get_partition("0x5").await.is_err());
+}
+
+#[tokio::test]
+async fn create_existing_partition() {
+    let (mock, ufm) = start(HOSTS).await;
+
+    ufm.create_partition(partition("0x5", Some(storage_qos())), vec![])
+        .await
+        .unwrap();
+    assert!(ufm
+        .create_partition(partition("0x5", Some(storage_qos())), vec![])
+        .await
+        .is_err());
+    assert_eq!(
+        mock.requests()
+            .iter()
+            .filter(|r| *r == "POST /resources/pkeys/add")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn partition_qos_values() {
+    for mtu in ["4k", "4KB", "4096", "4"] {
+        assert_eq!(mtu.parse::<IbMtu>().unwrap(), IbMtu::Mtu4K);
+    }
+    assert!("1k".parse::<IbMtu>().is_err());
+    assert_eq!(IbMtu::Mtu4K.bytes(), 4096);
+
+    for rate in ["HDR", "200", "200g", "200 Gb/s"] {
+        assert_eq!(rate.parse::<IbRate>().unwrap(), IbRate::Rate200);
+    }
+    assert_eq!("2.5".parse::<IbRate>().unwrap(), IbRate::Rate2_5);
+    assert!("42".parse::<IbRate>().is_err());
+
+    // The wire format of UFM.
+    let qos: PartitionQoS =
+        serde_json::from_str(r#"{"mtu_limit":4,"service_level":3,"rate_limit":100.0}"#).unwrap();
+    assert_eq!(qos.mtu_limit, IbMtu::Mtu4K);
+    assert_eq!(qos.rate_limit, IbRate::Rate100);
+    assert_eq!(
+        serde_json::to_string(&qos).unwrap(),
+        r#"{"mtu_limit":4,"service_level":3,"rate_limit":100.0}"#
+    );
+
+    // The values which UFM replied are kept even if they're not supported, but they're not
+    // sent back to UFM.
+    let qos: PartitionQoS =
+        serde_json::from_str(r#"{"mtu_limit":8,"service_level":0,"rate_limit":400.0}"#).unwrap();
+    assert_eq!(qos.mtu_limit, IbMtu::Other(8));
+    assert_eq!(qos.rate_limit, IbRate::Other(400000));
+    assert_eq!(
+        serde_json::to_string(&qos).unwrap(),
+        r#"{"mtu_limit":8,"service_level":0,"rate_limit":400.0}"#
+    );
+    let err = qos.validate().unwrap_err();
+    assert!(err.to_string().contains("unsupported mtu '8k'"), "{}", err);
+    let err = PartitionQoS {
+        mtu_limit: IbMtu::Mtu4K,
+        ..qos
+    }
+    .validate()
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported rate limit '400'"),
+        "{}",
+        err
+    );
+    for qos in [
+        r#"{"mtu_limit":2.5,"service_level":0,"rate_limit":100.0}"#,
+        r#"{"mtu_limit":4,"service_level":0,"rate_limit":-1.0}"#,
+    ] {
+        assert!(serde_json::from_str::<PartitionQoS>(qos).is_err());
+    }
+}
+
+#[tokio::test]
+async fn reject_invalid_service_level() {
+    let (mock, ufm) = start(HOSTS).await;
+    let mut p = partition("0x5", Some(storage_qos()));
+    p.qos = Some(PartitionQoS {
+        service_level: 16,
+        ..PartitionQoS::default()
+    });
+
+    assert!(matches!(
+        ufm.create_partition(p, vec![]).await,
+        Err(UFMError::InvalidConfig(_))
+    ));
+    assert!(!mock.requests().iter().any(|r| r.starts_with("POST")));
+}
+
+#[tokio::test]
+async fn create_partition_rollback() {
+    let (mock, ufm) = start(HOSTS).await;
+    let p = partition("0x6", Some(storage_qos()));
+
+    mock.inject_error(Method::POST, "/resources/pkeys", 500);
+    let res = ufm
+        .create_partition(p.clone(), vec![port(GUID_1, PortMembership::Full, true)])
+        .await;
+
+    assert!(res.is_err());
+    assert!(mock.partition(p.pkey).is_none());
+    assert!(mock
+        .requests()
+        .contains(&"DELETE /resources/pkeys/0x6".to_string()));
+}
+
+#[tokio::test]
+async fn bind_ports_by_group() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_vport(&mock);
+    let p = partition("0x7", Some(storage_qos()));
+
+    mock.inject_error(Method::POST, "/resources/pkeys", 500);
+    let results = ufm
+        .bind_ports(
+            p.clone(),
+            vec![
+                port(GUID_1, PortMembership::Full, true),
+                port(GUID_2, PortMembership::Limited, true),
+                port(VGUID, PortMembership::Full, true),
+            ],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(!results[0].is_ok());
+    assert_eq!(results[0].guids, vec![guid(GUID_1), guid(VGUID)]);
+    assert!(results[1].is_ok());
+    assert_eq!(results[1].guids, vec![guid(GUID_2)]);
+
+    let guids = mock.partition(p.pkey).unwrap().guids;
+    assert_eq!(guids.len(), 1);
+    assert_eq!(guids[0].membership, PortMembership::Limited);
+}
+
+#[tokio::test]
+async fn list_vports_of_parent() {
+    let (mock, ufm) = start(HOSTS).await;
+    add_vport(&mock);
+
+    assert_eq!(ufm.list_vports(None).await.unwrap().len(), 1);
+    assert_eq!(ufm.list_vports(Some(guid(GUID_1))).await.unwrap().len(), 0);
+    assert_eq!(
+        ufm.list_vports(Some(guid(&GUID_2.to_uppercase())))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn injected_errors() {
+    let (mock, ufm) = start(HOSTS).await;
+
+    mock.inject_error(Method::GET, "/app/ufm_version", 401);
+    assert!(matches!(ufm.version().await, Err(UFMError::AuthFailure(_))));
+
+    mock.inject_error(Method::GET, "/app/ufm_version", 404);
+    assert!(matches!(ufm.version().await, Err(UFMError::NotFound(_))));
+
+    mock.inject_error(Method::GET, "/app/ufm_version", 500);
+    match ufm.version().await {
+        Err(UFMError::Http { status, body }) => {
+            assert_eq!(status, 500);
+            assert!(body.contains("injected error"));
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+
+    assert!(matches!(
+        ufm.get_partition("0x9").await,
+        Err(UFMError::NotFound(_))
+    ));
+    assert!(matches!(
+        ufm.delete_partition("0x9").await,
+        Err(UFMError::NotFound(_))
+    ));
+    assert!(matches!(
+        ufm.delete_partition("0x7fff").await,
+        Err(UFMError::Http { status: 400, .. })
+    ));
+}
+
+#[tokio::test]
+async fn retry_idempotent_requests() {
+    let (mock, ufm) = start(HOSTS).await;
+    let count = |req: &str| mock.requests().iter().filter(|r| *r == req).count();
+
+    mock.inject_error(Method::GET, "/app/ufm_version", 503);
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    assert_eq!(count("GET /app/ufm_version"), 2);
+
+    mock.inject_error(Method::POST, "/resources/pkeys/add", 503);
+    assert!(ufm
+        .create_partition(partition("0x5", Some(storage_qos())), vec![])
+        .await
+        .is_err());
+    assert_eq!(count("POST /resources/pkeys/add"), 1);
+
+    let ufm = sm::connect(UFMConfig {
+        policy: RestPolicy {
+            retries: 0,
+            ..RestPolicy::default()
+        },
+        ..mock.config()
+    })
+    .unwrap();
+    mock.inject_error(Method::GET, "/app/ufm_version", 503);
+    assert!(matches!(
+        ufm.version().await,
+        Err(UFMError::Http { status: 503, .. })
+    ));
+}
+
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn redact_credentials_in_logs() {
+    let (mock, ufm) = start(HOSTS).await;
+
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+    let ufm = sm::connect(UFMConfig {
+        username: None,
+        password: None,
+        token: Some(MOCK_TOKEN.to_string()),
+        ..mock.config()
+    })
+    .unwrap();
+    assert_eq!(ufm.version().await.unwrap(), MOCK_VERSION);
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("<redacted>"));
+    assert!(logs.contains("latency_ms"));
+    assert!(!logs.contains(MOCK_PASSWORD));
+    assert!(!logs.contains(MOCK_TOKEN));
+}
+
+#[tokio::test]
+async fn reconcile_desired_state() {
+    let (mock, ufm) = start(HOSTS).await;
+    ufm.create_partition(partition("0x9", Some(storage_qos())), vec![])
+        .await
+        .unwrap();
+
+    let desired = DesiredState::from_toml(&format!(
+        r#"
+        [[partition]]
+        pkey = "0x5"
+        name = "storage"
+        ipoib = true
+        qos = {{ mtu_limit = "4k", service_level = 0, rate_limit = "EDR" }}
+        members = [
+            {{ guid = "{}", membership = "full", index0 = true }},
+            {{ guid = "{}", membership = "limited" }},
+        ]
+        "#,
+        GUID_1, GUID_2
+    ))
+    .unwrap();
+
+    let changes = ufm.reconcile(&desired, true).await.unwrap();
+    assert_eq!(changes.len(), 2);
+
+    assert_eq!(mock.partition(pkey("0x5")).unwrap().guids.len(), 2);
+    assert!(mock.partition(pkey("0x9")).is_none());
+
+    assert!(ufm.diff(&desired, true).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reconcile_ipoib() {
+    let (mock, ufm) = start(HOSTS).await;
+    ufm.create_partition(partition("0x9", Some(storage_qos())), vec![])
+        .await
+        .unwrap();
+    ufm.create_partition(
+        partition("0xa", Some(storage_qos())),
+        vec![port(GUID_1, PortMembership::Full, true)],
+    )
+    .await
+    .unwrap();
+
+    let desired = DesiredState::from_toml(&format!(
+        r#"
+        [[partition]]
+        pkey = "0x9"
+        ipoib = false
+
+        [[partition]]
+        pkey = "0xa"
+        ipoib = false
+        members = [{{ guid = "{}", membership = "full", index0 = true }}]
+        "#,
+        GUID_1
+    ))
+    .unwrap();
+
+    // IPoIB is changed by re-binding the ports, so the partition without ports is left as is.
+    let changes = ufm.diff(&desired, false).await.unwrap();
+    assert_eq!(changes.len(), 1, "{:?}", changes);
+    assert!(
+        matches!(&changes[0], Change::UpdateIPoIB { partition, ports }
+            if partition.pkey == pkey("0xa") && ports.len() == 1)
+    );
+
+    ufm.apply(&changes).await.unwrap();
+    assert!(!mock.partition(pkey("0xa")).unwrap().ipoib);
+    assert!(mock.partition(pkey("0x9")).unwrap().ipoib);
+}
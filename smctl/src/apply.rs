@@ -0,0 +1,35 @@
+use std::fs;
+
+use libonm::sm::{self, DesiredState, SMConfig, UFMError};
+
+use crate::plan::{self, DryRun};
+
+pub async fn run(
+    conf: SMConfig,
+    file: &str,
+    prune: bool,
+    dry_run: Option<DryRun>,
+) -> Result<(), UFMError> {
+    let contents = fs::read_to_string(file)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", file, e)))?;
+    let desired = DesiredState::from_toml(&contents)?;
+
+    let sm = sm::build(conf)?;
+    let changes = sm.diff(&desired, prune).await?;
+
+    if let Some(format) = dry_run {
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
+
+    if changes.is_empty() {
+        println!("No changes.");
+        return Ok(());
+    }
+
+    for change in changes {
+        sm.apply(std::slice::from_ref(&change)).await?;
+        println!("{}", change);
+    }
+
+    Ok(())
+}
@@ -1,25 +1,106 @@
-use libonm::sm::{self, Partition, PartitionKey, PortConfig, PortMembership, UFMConfig, UFMError};
-
-pub async fn run(conf: UFMConfig, pkey: &str, guids: &Vec<String>) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
-
-    let mut pbs = vec![];
-    for g in guids {
-        pbs.push(PortConfig {
-            guid: g.to_string(),
-            index0: true,
-            membership: PortMembership::Full,
-        })
-    }
+use libonm::sm::{
+    self, BindResult, Change, Partition, PartitionKey, PortConfig, PortMembership, SMConfig,
+    UFMError,
+};
+
+use crate::plan::{self, DryRun};
+
+pub struct BindOptions {
+    pub pkey: String,
+    pub index0: bool,
+    pub membership: String,
+    pub guids: Vec<String>,
+}
+
+pub async fn run(
+    conf: SMConfig,
+    opt: &BindOptions,
+    dry_run: Option<DryRun>,
+) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
 
-    let p = Partition {
-        name: "".to_string(),
-        pkey: PartitionKey::try_from(pkey.to_owned())?,
-        ipoib: false,
-        qos: None,
+    let membership = PortMembership::try_from(opt.membership.clone())?;
+    let pbs = opt
+        .guids
+        .iter()
+        .map(|g| parse_port(g, opt.index0, &membership))
+        .collect::<Result<Vec<PortConfig>, UFMError>>()?;
+
+    // UFM sets IPoIB of the partition by the request of the ports, so keep the current one.
+    let pkey = PartitionKey::try_from(opt.pkey.to_owned())?;
+    let p = match sm.get_partition(&pkey.to_string()).await {
+        Ok(p) => p,
+        Err(UFMError::NotFound(_)) => Partition {
+            name: "".to_string(),
+            pkey,
+            ipoib: false,
+            qos: None,
+        },
+        Err(e) => return Err(e),
     };
 
-    ufm.bind_ports(p, pbs).await?;
+    if let Some(format) = dry_run {
+        let changes = vec![Change::BindPorts {
+            partition: p,
+            ports: pbs,
+        }];
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
+
+    let results = sm.bind_ports(p, pbs).await?;
+    check_results(&results)?;
 
     Ok(())
 }
+
+/// Parse the port in the format of `GUID[:membership]`, e.g. `1070fd0300176625:limited`;
+/// the default membership is used if it's not specified.
+pub fn parse_port(
+    port: &str,
+    index0: bool,
+    membership: &PortMembership,
+) -> Result<PortConfig, UFMError> {
+    if let Some((guid, m)) = port.rsplit_once(':') {
+        if let Ok(m) = PortMembership::try_from(m) {
+            return Ok(PortConfig {
+                guid: guid.parse()?,
+                index0,
+                membership: m,
+            });
+        }
+    }
+
+    Ok(PortConfig {
+        guid: port.parse()?,
+        index0,
+        membership: membership.clone(),
+    })
+}
+
+/// Print the groups which UFM failed to bind, and return an error if there's any.
+pub fn check_results(results: &[BindResult]) -> Result<(), UFMError> {
+    let failed: Vec<&BindResult> = results.iter().filter(|r| !r.is_ok()).collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    for r in &failed {
+        eprintln!(
+            "Failed to bind <{}> as {} member (index0: {}): {}",
+            r.guids
+                .iter()
+                .map(|g| g.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+            r.membership,
+            r.index0,
+            r.error.clone().unwrap_or_default()
+        );
+    }
+
+    Err(UFMError::Unknown(format!(
+        "failed to bind {} of {} port groups",
+        failed.len(),
+        results.len()
+    )))
+}
@@ -0,0 +1,169 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use libonm::sm::{self, PortCounters, PortRates, SMConfig, UFMError};
+use libonm::Guid;
+
+use crate::output::{self, Output, Render, Table};
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct CounterList(Vec<PortCounters>);
+
+impl Render for CounterList {
+    fn table(&self, wide: bool) -> String {
+        let mut headers = vec![
+            "GUID",
+            "SystemName",
+            "Speed",
+            "Width",
+            "SymbolErrors",
+            "LinkDowned",
+            "XmitData",
+            "RcvData",
+        ];
+        if wide {
+            headers.extend(["XmitPkts", "RcvPkts", "XmitWait", "XmitTimeCong"]);
+        }
+
+        let mut table = Table::new(&headers);
+        for c in &self.0 {
+            let mut row = vec![
+                c.guid.to_string(),
+                c.system_name.clone(),
+                c.active_speed.clone(),
+                c.active_width.clone(),
+                c.symbol_errors.to_string(),
+                c.link_downed.to_string(),
+                bytes(c.xmit_data as f64 * 4.0),
+                bytes(c.rcv_data as f64 * 4.0),
+            ];
+            if wide {
+                row.extend([
+                    c.xmit_packets.to_string(),
+                    c.rcv_packets.to_string(),
+                    c.xmit_wait.to_string(),
+                    c.xmit_time_cong.to_string(),
+                ]);
+            }
+            table.add_row(row);
+        }
+
+        table.to_string()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct RateList(Vec<PortRates>);
+
+impl Render for RateList {
+    fn table(&self, wide: bool) -> String {
+        let mut headers = vec![
+            "GUID",
+            "SystemName",
+            "SymbolErrors",
+            "LinkDowned",
+            "Xmit/s",
+            "Rcv/s",
+        ];
+        if wide {
+            headers.extend(["XmitPkts/s", "RcvPkts/s", "XmitWait/s", "XmitTimeCong/s"]);
+        }
+
+        let mut table = Table::new(&headers);
+        for r in &self.0 {
+            let mut row = vec![
+                r.guid.to_string(),
+                r.system_name.clone(),
+                r.symbol_errors.to_string(),
+                r.link_downed.to_string(),
+                bytes(r.xmit_bytes),
+                bytes(r.rcv_bytes),
+            ];
+            if wide {
+                row.extend([
+                    format!("{:.0}", r.xmit_packets),
+                    format!("{:.0}", r.rcv_packets),
+                    format!("{:.0}", r.xmit_wait),
+                    format!("{:.0}", r.xmit_time_cong),
+                ]);
+            }
+            table.add_row(row);
+        }
+
+        table.to_string()
+    }
+}
+
+// The bytes in the decimal units, e.g. 1.5 GB.
+fn bytes(n: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut n = n;
+    let mut unit = 0;
+    while n >= 1000.0 && unit < UNITS.len() - 1 {
+        n /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", n, UNITS[unit])
+    } else {
+        format!("{:.1} {}", n, UNITS[unit])
+    }
+}
+
+pub async fn run(
+    conf: SMConfig,
+    pkey: &str,
+    watch: Option<u64>,
+    format: Output,
+) -> Result<(), UFMError> {
+    let SMConfig::Ufm(conf) = conf else {
+        return Err(UFMError::InvalidConfig(
+            "the port counters are only available in UFM".to_string(),
+        ));
+    };
+    let ufm = sm::connect(conf)?;
+    let partition = ufm.get_partition(pkey).await?;
+    let guids: Vec<Guid> = ufm
+        .list_port(partition.pkey)
+        .await?
+        .iter()
+        .map(|p| p.guid)
+        .collect();
+
+    let mut earlier = ufm.list_port_counters(&guids).await?;
+    let mut sampled = Instant::now();
+    output::print(format, &CounterList(earlier.clone()))?;
+
+    let Some(interval) = watch else {
+        return Ok(());
+    };
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+
+        let later = ufm.list_port_counters(&guids).await?;
+        let now = Instant::now();
+        let rates = RateList(rates(&earlier, &later, now - sampled));
+
+        println!();
+        output::print(format, &rates)?;
+
+        (earlier, sampled) = (later, now);
+    }
+}
+
+// The rates of the ports in both samples, as a port may be missing in a snapshot.
+fn rates(earlier: &[PortCounters], later: &[PortCounters], elapsed: Duration) -> Vec<PortRates> {
+    later
+        .iter()
+        .filter_map(|l| {
+            let e = earlier.iter().find(|e| e.guid == l.guid)?;
+            Some(PortRates::between(e, l, elapsed))
+        })
+        .collect()
+}
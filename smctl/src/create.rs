@@ -1,33 +1,57 @@
-use libonm::sm::{self, Partition, PartitionKey, PortConfig, PortMembership, UFMConfig, UFMError};
+use libonm::sm::{
+    self, Change, IbMtu, IbRate, Partition, PartitionKey, PartitionQoS, PortConfig, PortMembership,
+    SMConfig, UFMError,
+};
+
+use crate::bind;
+use crate::plan::{self, DryRun};
 
 pub struct CreateOptions {
     pub pkey: String,
+    pub name: String,
     pub ipoib: bool,
     pub index0: bool,
     pub membership: String,
+    pub mtu: IbMtu,
+    pub service_level: u8,
+    pub rate_limit: IbRate,
     pub guids: Vec<String>,
 }
 
-pub async fn run(conf: UFMConfig, opt: &CreateOptions) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
+pub async fn run(
+    conf: SMConfig,
+    opt: &CreateOptions,
+    dry_run: Option<DryRun>,
+) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
 
-    let mut pbs = vec![];
-    for g in &opt.guids {
-        pbs.push(PortConfig {
-            guid: g.to_string(),
-            index0: opt.index0,
-            membership: PortMembership::try_from(opt.membership.clone())?,
-        })
-    }
+    let membership = PortMembership::try_from(opt.membership.clone())?;
+    let pbs = opt
+        .guids
+        .iter()
+        .map(|g| bind::parse_port(g, opt.index0, &membership))
+        .collect::<Result<Vec<PortConfig>, UFMError>>()?;
 
     let p = Partition {
-        name: "".to_string(),
+        name: opt.name.clone(),
         pkey: PartitionKey::try_from(opt.pkey.clone())?,
         ipoib: opt.ipoib,
-        qos: None,
+        qos: Some(PartitionQoS {
+            mtu_limit: opt.mtu,
+            service_level: opt.service_level,
+            rate_limit: opt.rate_limit,
+        }),
     };
 
-    ufm.bind_ports(p, pbs).await?;
+    if let Some(format) = dry_run {
+        let changes = vec![Change::CreatePartition {
+            partition: p,
+            ports: pbs,
+        }];
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
+
+    sm.create_partition(p, pbs).await?;
 
     Ok(())
 }
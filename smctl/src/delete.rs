@@ -1,8 +1,18 @@
-use libonm::sm::{self, UFMConfig, UFMError};
+use libonm::sm::{self, Change, PartitionKey, SMConfig, UFMError};
 
-pub async fn run(conf: UFMConfig, pkey: &str) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
-    ufm.delete_partition(pkey).await?;
+use crate::plan::{self, DryRun};
+
+pub async fn run(conf: SMConfig, pkey: &str, dry_run: Option<DryRun>) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+
+    if let Some(format) = dry_run {
+        let changes = vec![Change::DeletePartition {
+            pkey: PartitionKey::try_from(pkey)?,
+        }];
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
+
+    sm.delete_partition(pkey).await?;
 
     Ok(())
 }
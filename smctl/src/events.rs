@@ -0,0 +1,190 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::{pin_mut, Stream, StreamExt};
+use serde::Serialize;
+
+use libonm::sm::{self, Alarm, Event, EventFilter, SMConfig, Severity, UFMError};
+use libonm::Guid;
+
+use crate::output::{self, Output, Render, Table};
+
+pub struct EventsOptions {
+    pub alarms: bool,
+    pub severity: Option<Severity>,
+    pub guid: Option<Guid>,
+    pub since: Option<Duration>,
+    pub follow: bool,
+    pub interval: u64,
+}
+
+/// An entry of the events or the alarms, which is printed in a table or one per line.
+trait Entry: Serialize {
+    const HEADERS: &'static [&'static str];
+    const WIDE_HEADERS: &'static [&'static str];
+
+    fn row(&self, wide: bool) -> Vec<String>;
+}
+
+impl Entry for Event {
+    const HEADERS: &'static [&'static str] =
+        &["ID", "Timestamp", "Severity", "Type", "Object", "Name"];
+    const WIDE_HEADERS: &'static [&'static str] = &["Description"];
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![
+            self.id.to_string(),
+            self.timestamp.clone(),
+            self.severity.to_string(),
+            self.object_type.clone(),
+            self.object_name.clone(),
+            self.name.clone(),
+        ];
+        if wide {
+            row.push(self.description.clone());
+        }
+
+        row
+    }
+}
+
+impl Entry for Alarm {
+    const HEADERS: &'static [&'static str] =
+        &["ID", "Timestamp", "Severity", "Type", "Object", "Name"];
+    const WIDE_HEADERS: &'static [&'static str] = &["Counter", "Reason"];
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![
+            self.id.to_string(),
+            self.timestamp.clone(),
+            self.severity.to_string(),
+            self.object_type.clone(),
+            self.object_name.clone(),
+            self.name.clone(),
+        ];
+        if wide {
+            row.extend([self.counter.to_string(), self.reason.clone()]);
+        }
+
+        row
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct EntryList<T: Entry>(Vec<T>);
+
+impl<T: Entry> Render for EntryList<T> {
+    fn table(&self, wide: bool) -> String {
+        let mut headers = T::HEADERS.to_vec();
+        if wide {
+            headers.extend(T::WIDE_HEADERS);
+        }
+
+        let mut table = Table::new(&headers);
+        for entry in &self.0 {
+            table.add_row(entry.row(wide));
+        }
+
+        table.to_string()
+    }
+}
+
+// Print the entry as it's followed: a line of the columns in the tables, a line of JSON, or
+// a document of YAML.
+fn print_entry<T: Entry>(format: Output, entry: &T) -> Result<(), UFMError> {
+    match format {
+        Output::Table | Output::Wide => println!("{}", entry.row(format.is_wide()).join("  ")),
+        Output::Json => {
+            let json =
+                serde_json::to_string(entry).map_err(|e| UFMError::Unknown(e.to_string()))?;
+            println!("{}", json);
+        }
+        Output::Yaml => {
+            let yaml =
+                serde_yaml::to_string(entry).map_err(|e| UFMError::Unknown(e.to_string()))?;
+            print!("---\n{}", yaml);
+        }
+    }
+
+    Ok(())
+}
+
+async fn follow<T: Entry>(
+    entries: impl Stream<Item = Result<T, UFMError>>,
+    format: Output,
+) -> Result<(), UFMError> {
+    pin_mut!(entries);
+    while let Some(entry) = entries.next().await {
+        match entry {
+            Ok(entry) => print_entry(format, &entry)?,
+            // Keep following, as UFM may be back at the next poll.
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run(conf: SMConfig, opt: &EventsOptions, format: Output) -> Result<(), UFMError> {
+    let SMConfig::Ufm(conf) = conf else {
+        return Err(UFMError::InvalidConfig(
+            "the events are only available in UFM".to_string(),
+        ));
+    };
+
+    // The period must not reach before the epoch, e.g. 100000000d.
+    let since = opt
+        .since
+        .map(|d| {
+            SystemTime::now()
+                .checked_sub(d)
+                .filter(|t| *t >= UNIX_EPOCH)
+                .ok_or_else(|| {
+                    UFMError::InvalidConfig(format!("--since {}s is before the epoch", d.as_secs()))
+                })
+        })
+        .transpose()?;
+
+    let ufm = sm::connect(conf)?;
+    let filter = EventFilter {
+        severity: opt.severity,
+        guid: opt.guid,
+        since,
+        until: None,
+    };
+
+    if opt.follow {
+        let interval = Duration::from_secs(opt.interval);
+        return if opt.alarms {
+            follow(ufm.watch_alarms(filter, interval), format).await
+        } else {
+            follow(ufm.watch_events(filter, interval), format).await
+        };
+    }
+
+    if opt.alarms {
+        output::print(format, &EntryList(ufm.list_alarms(&filter).await?))
+    } else {
+        output::print(format, &EntryList(ufm.list_events(&filter).await?))
+    }
+}
+
+/// Parse the period, e.g. 90s, 30m, 2h or 1d.
+pub fn parse_period(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid period '{}', e.g. 90s, 30m, 2h or 1d", s);
+
+    let s = s.trim();
+    let unit = match s.chars().last() {
+        Some('s') => 1,
+        Some('m') => 60,
+        Some('h') => 3600,
+        Some('d') => 86400,
+        _ => return Err(invalid()),
+    };
+    let n: u64 = s[..s.len() - 1].parse().map_err(|_| invalid())?;
+    let secs = n
+        .checked_mul(unit)
+        .ok_or_else(|| format!("period '{}' is too long", s))?;
+
+    Ok(Duration::from_secs(secs))
+}
@@ -0,0 +1,60 @@
+use std::fs;
+
+use clap::ValueEnum;
+
+use libonm::sm::{self, PartitionSnapshot, SMConfig, UFMError};
+
+/// The format of the snapshot of the partitions.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SnapshotFormat {
+    Json,
+    Toml,
+}
+
+impl SnapshotFormat {
+    /// The format if it's set, or by the extension of the file: TOML for `.toml`, and JSON
+    /// otherwise.
+    pub fn resolve(format: Option<SnapshotFormat>, file: Option<&str>) -> Self {
+        match (format, file) {
+            (Some(format), _) => format,
+            (None, Some(file)) if file.to_lowercase().ends_with(".toml") => SnapshotFormat::Toml,
+            _ => SnapshotFormat::Json,
+        }
+    }
+}
+
+pub async fn run(
+    conf: SMConfig,
+    file: Option<&str>,
+    format: Option<SnapshotFormat>,
+) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+    let snapshot = sm.export().await?;
+
+    let contents = match SnapshotFormat::resolve(format, file) {
+        SnapshotFormat::Json => format!("{}\n", snapshot.to_json()?),
+        SnapshotFormat::Toml => snapshot.to_toml()?,
+    };
+
+    let Some(file) = file else {
+        print!("{}", contents);
+        return Ok(());
+    };
+
+    fs::write(file, contents)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to write {}: {}", file, e)))?;
+    println!(
+        "Exported {} partitions to {}.",
+        snapshot.partitions.len(),
+        file
+    );
+
+    Ok(())
+}
+
+pub fn parse(contents: &str, format: SnapshotFormat) -> Result<PartitionSnapshot, UFMError> {
+    match format {
+        SnapshotFormat::Json => PartitionSnapshot::from_json(contents),
+        SnapshotFormat::Toml => PartitionSnapshot::from_toml(contents),
+    }
+}
@@ -1,16 +1,30 @@
-use libonm::sm::{self, UFMConfig, UFMError};
+use libonm::sm::{self, Configuration, SMConfig, UFMError};
 
-pub async fn run(conf: UFMConfig) -> Result<(), UFMError> {
+use crate::output::{self, Details, Output, Render};
+
+impl Render for Configuration {
+    fn table(&self, _wide: bool) -> String {
+        let mut details = Details::new();
+        details.add("subnet prefix", &self.subnet_prefix);
+        details.add("m_key", &self.m_key);
+        details.add("m_key_per_port", self.m_key_per_port);
+        details.add("sm_key", &self.sm_key);
+        details.add("sa_key", &self.sa_key);
+        details.add("qos", self.qos);
+        details.add("log_file", &self.log_file);
+
+        details.to_string()
+    }
+}
+
+pub async fn run(conf: SMConfig, format: Output) -> Result<(), UFMError> {
+    let SMConfig::Ufm(conf) = conf else {
+        return Err(UFMError::InvalidConfig(
+            "the configuration is only available in UFM".to_string(),
+        ));
+    };
     let ufm = sm::connect(conf)?;
     let config = ufm.get_configuration().await?;
 
-    println!("subnet prefix  : {}", config.subnet_prefix);
-    println!("m_key          : {}", config.m_key);
-    println!("m_key_per_port : {}", config.m_key_per_port);
-    println!("sm_key         : {}", config.sm_key);
-    println!("sa_key         : {}", config.sa_key);
-    println!("qos            : {}", config.qos);
-    println!("log_file       : {}", config.log_file);
-
-    Ok(())
+    output::print(format, &config)
 }
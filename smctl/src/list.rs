@@ -1,29 +1,56 @@
-use libonm::sm::{self, UFMConfig, UFMError};
-
-pub async fn run(conf: UFMConfig) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
-    let ps = ufm.list_partition().await?;
-
-    println!(
-        "{:<15}{:<10}{:<10}{:<10}{:<10}{:<10}",
-        "Name", "Pkey", "IPoIB", "MTU", "Rate", "Level"
-    );
-
-    for p in ps {
-        let qos = p
-            .qos
-            .ok_or(UFMError::InvalidConfig("no partition qos".to_string()))?;
-
-        println!(
-            "{:<15}{:<10}{:<10}{:<10}{:<10}{:<10}",
-            p.name,
-            p.pkey.to_string(),
-            p.ipoib,
-            qos.mtu_limit,
-            qos.rate_limit,
-            qos.service_level
-        )
+use serde::Serialize;
+
+use libonm::sm::{self, Partition, SMConfig, UFMError};
+
+use crate::output::{self, or_none, Output, Render, Table};
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct PartitionList {
+    partitions: Vec<Partition>,
+    // The number of the ports of each partition, only for the wide output.
+    #[serde(skip)]
+    ports: Vec<usize>,
+}
+
+impl Render for PartitionList {
+    fn table(&self, wide: bool) -> String {
+        let mut headers = vec!["Name", "Pkey", "IPoIB", "MTU", "Rate", "Level"];
+        if wide {
+            headers.push("Ports");
+        }
+
+        let mut table = Table::new(&headers);
+        for (i, p) in self.partitions.iter().enumerate() {
+            let qos = p.qos.as_ref();
+            let mut row = vec![
+                p.name.clone(),
+                p.pkey.to_string(),
+                p.ipoib.to_string(),
+                or_none(qos.map(|q| q.mtu_limit)),
+                or_none(qos.map(|q| q.rate_limit)),
+                or_none(qos.map(|q| q.service_level)),
+            ];
+            if wide {
+                row.push(or_none(self.ports.get(i)));
+            }
+            table.add_row(row);
+        }
+
+        table.to_string()
+    }
+}
+
+pub async fn run(conf: SMConfig, format: Output) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+    let partitions = sm.list_partition().await?;
+
+    let mut ports = vec![];
+    if format.is_wide() {
+        for p in &partitions {
+            ports.push(sm.list_port(p.pkey).await?.len());
+        }
     }
 
-    Ok(())
+    output::print(format, &PartitionList { partitions, ports })
 }
@@ -1,12 +1,29 @@
-use clap::{Parser, Subcommand};
+use std::time::Duration;
 
-use libonm::sm::{UFMCert, UFMConfig, UFMError};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use tracing_subscriber::{filter::EnvFilter, filter::LevelFilter, fmt, prelude::*};
 
+use libonm::cred::{self, CredentialConfig};
+use libonm::sm::{
+    Conflict, IbMtu, IbRate, OpenSMConfig, PortAction, PortId, SMConfig, Severity, UFMCert,
+    UFMConfig, UFMError,
+};
+use libonm::{Guid, RestFixture, RestPolicy};
+
+mod apply;
 mod bind;
+mod counters;
 mod create;
 mod delete;
+mod events;
+mod export;
+mod import;
 mod info;
 mod list;
+mod output;
+mod plan;
+mod port;
+mod topology;
 mod unbind;
 mod update;
 mod version;
@@ -18,6 +35,9 @@ mod view;
 #[command(version = "0.1.0")]
 #[command(about = "SubnetManager command line", long_about = None)]
 struct Options {
+    /// The subnet manager of the fabric
+    #[clap(long, env = "SM_BACKEND", value_enum, default_value_t = Backend::Ufm)]
+    backend: Backend,
     #[clap(long, env = "UFM_ADDRESS")]
     ufm_address: Option<String>,
     #[clap(long, env = "UFM_USERNAME")]
@@ -26,16 +46,77 @@ struct Options {
     ufm_password: Option<String>,
     #[clap(long, env = "UFM_TOKEN")]
     ufm_token: Option<String>,
+    /// The name of the credential of the password, or of the token without username
+    #[clap(long, env = "UFM_CREDENTIAL")]
+    ufm_credential: Option<String>,
+    /// The provider of the credentials: env[:<prefix>], file:<dir>, command:<password_command> or keyring
+    #[clap(long, env = "ONM_CREDENTIAL_PROVIDER", default_value = "env")]
+    credential_provider: CredentialConfig,
     #[clap(long, env = "UFM_CA_CRT")]
     ufm_ca_crt: Option<String>,
     #[clap(long, env = "UFM_TLS_KEY")]
     ufm_tls_key: Option<String>,
     #[clap(long, env = "UFM_TLS_CRT")]
     ufm_tls_crt: Option<String>,
+    /// The timeout of the requests to UFM in seconds [default: 60]
+    #[clap(long, env = "UFM_TIMEOUT")]
+    ufm_timeout: Option<u64>,
+    /// The timeout to connect to UFM in seconds [default: 10]
+    #[clap(long, env = "UFM_CONNECT_TIMEOUT")]
+    ufm_connect_timeout: Option<u64>,
+    /// The max retries of the idempotent requests to UFM, 0 to not retry [default: 3]
+    #[clap(long, env = "UFM_RETRIES")]
+    ufm_retries: Option<u32>,
+    /// Allow the password or token to be sent to an http:// UFM_ADDRESS in plain text
+    #[clap(long, env = "UFM_INSECURE_HTTP")]
+    ufm_insecure_http: bool,
+    /// Wait up to SECONDS for the jobs of the changes which UFM runs in the background, e.g. on large fabrics
+    #[clap(long, env = "UFM_WAIT", value_name = "SECONDS")]
+    ufm_wait: Option<u64>,
+    /// Append the requests to UFM and their responses to the fixture file, with the credentials scrubbed
+    #[clap(long, env = "UFM_RECORD", conflicts_with = "ufm_replay")]
+    ufm_record: Option<String>,
+    /// Reply the requests to UFM by the responses in the fixture file instead of UFM
+    #[clap(long, env = "UFM_REPLAY")]
+    ufm_replay: Option<String>,
+    /// The partitions.conf of OpenSM [default: /etc/opensm/partitions.conf]
+    #[clap(long, env = "OPENSM_PARTITIONS")]
+    opensm_partitions: Option<String>,
+    /// The command to make OpenSM re-read partitions.conf [default: pkill -HUP -x opensm]
+    #[clap(long, env = "OPENSM_REREAD_COMMAND")]
+    opensm_reread_command: Option<String>,
+    /// Print the plan of create/update/bind/unbind/delete/apply/import instead of applying it
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "text"
+    )]
+    dry_run: Option<plan::DryRun>,
+    /// The format of the output of list, view, info, topology, counters and events
+    #[clap(
+        short,
+        long,
+        global = true,
+        env = "SMCTL_OUTPUT",
+        value_enum,
+        default_value_t = output::Output::Table
+    )]
+    output: output::Output,
     #[clap(subcommand)]
     command: Option<Commands>,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Backend {
+    /// NVIDIA UFM by its REST API
+    Ufm,
+    /// OpenSM by its partitions.conf
+    Opensm,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// View the detail of the partition
@@ -46,10 +127,55 @@ enum Commands {
     },
     /// List all partitions
     List,
-    /// Get the version of UFM
+    /// Get the version of the subnet manager
     Version,
     /// Get the configuration information of UFM
     Info,
+    /// Show the systems and links of the fabric in UFM, or the uplinks of a host
+    Topology {
+        /// The name or GUID of the host, or the GUID of one of its ports, to show the paths
+        /// from it to its leaf and spine switches
+        #[arg(long)]
+        host: Option<String>,
+        /// Export the graph of the fabric in the DOT language of Graphviz
+        #[arg(long, conflicts_with = "host")]
+        dot: bool,
+    },
+    /// Show the counters of the ports of the partition in UFM
+    Counters {
+        /// The pkey of the partition
+        #[arg(short, long)]
+        pkey: String,
+        /// Sample the counters every SECONDS and show their rates, until interrupted
+        #[arg(short, long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        watch: Option<u64>,
+    },
+    /// List the events of UFM, or its open alarms
+    Events {
+        /// List the open alarms instead of the events
+        #[arg(long)]
+        alarms: bool,
+        /// The least severity, e.g. warning or critical
+        #[arg(long)]
+        severity: Option<Severity>,
+        /// The GUID of the object, e.g. of a port or of its system
+        #[arg(long)]
+        guid: Option<Guid>,
+        /// Only the entries of the last period, e.g. 30m, 2h or 1d
+        #[arg(long, value_parser = events::parse_period)]
+        since: Option<Duration>,
+        /// Keep polling UFM and print the new entries, until interrupted
+        #[arg(short, long)]
+        follow: bool,
+        /// The interval of the polls in seconds
+        #[arg(long, default_value_t = 5, requires = "follow", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Disable, enable or reset a port in UFM
+    Port {
+        #[command(subcommand)]
+        action: PortCommand,
+    },
     /// Delete the partition
     Delete {
         /// The pkey of the partition to delete
@@ -61,17 +187,36 @@ enum Commands {
         /// The pkey for the new partition
         #[arg(short, long)]
         pkey: String,
+        /// The name of the new partition
+        #[arg(short, long)]
+        name: Option<String>,
         /// The IPOverIB of the new partition
         #[arg(long, default_value_t = true)]
         ipoib: bool,
         /// The Index0 of the new partition
-        #[arg(long, default_value_t = true)]
+        #[arg(
+            long,
+            default_value_t = true,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
         index0: bool,
         /// The Membership of the new partition
         #[arg(short, long, default_value_t = String::from("full"))]
         membership: String,
+        /// The MTU of the new partition, e.g. 2k or 4k
+        #[arg(long, default_value_t = IbMtu::Mtu2K)]
+        mtu: IbMtu,
+        /// The ServiceLevel of the new partition, 0-15
+        #[arg(short, long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=15))]
+        service_level: u8,
+        /// The RateLimit of the new partition in Gb/s or by name, e.g. 100 or HDR
+        #[arg(short, long, default_value_t = IbRate::Rate2_5)]
+        rate_limit: IbRate,
 
-        /// The GUIDs of the new partition
+        /// The GUIDs of the new partition, in the format of GUID[:full|limited]
         #[arg(short, long)]
         guids: Vec<String>,
     },
@@ -83,15 +228,15 @@ enum Commands {
         /// The IPOverIB of the new partition
         #[arg(long, default_value_t = true)]
         ipoib: bool,
-        /// The MTU of the new partition
-        #[arg(long, default_value_t = 4)]
-        mtu: u16,
-        /// The ServiceLevel of the new partition
-        #[arg(short, long, default_value_t = 0)]
-        service_level: u8,
-        /// The RateLimit of the new partition
-        #[arg(short, long, default_value_t = 100f64)]
-        rate_limit: f64,
+        /// The MTU of the partition, e.g. 2k or 4k; unchanged if not set
+        #[arg(long)]
+        mtu: Option<IbMtu>,
+        /// The ServiceLevel of the partition, 0-15; unchanged if not set
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(0..=15))]
+        service_level: Option<u8>,
+        /// The RateLimit of the partition in Gb/s or by name, e.g. 100 or HDR; unchanged if not set
+        #[arg(short, long)]
+        rate_limit: Option<IbRate>,
     },
 
     /// Bind ports to the partition
@@ -99,11 +244,57 @@ enum Commands {
         /// The pkey of the partition
         #[arg(short, long)]
         pkey: String,
-        /// A list of GUID to bind
+        /// The Index0 of the ports, e.g. --index0=false
+        #[arg(
+            long,
+            default_value_t = true,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
+        index0: bool,
+        /// The default Membership of the ports
+        #[arg(short, long, default_value_t = String::from("full"))]
+        membership: String,
+        /// A list of GUID to bind, in the format of GUID[:full|limited]
         #[arg(short, long)]
         guids: Vec<String>,
     },
 
+    /// Apply the desired partitions in the file to the subnet manager
+    Apply {
+        /// The TOML file of the desired partitions
+        #[arg(short, long)]
+        file: String,
+        /// Delete the partitions which are not in the file, except the default partition
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+    },
+
+    /// Export the partitions with their QoS and members to a snapshot, e.g. before an upgrade of UFM
+    Export {
+        /// The file of the snapshot; the snapshot is printed if not set
+        #[arg(short, long)]
+        file: Option<String>,
+        /// The format of the snapshot [default: by the extension of the file, or json]
+        #[arg(long, value_enum)]
+        format: Option<export::SnapshotFormat>,
+    },
+
+    /// Recreate the partitions from a snapshot
+    Import {
+        /// The file of the snapshot
+        #[arg(short, long)]
+        file: String,
+        /// The format of the snapshot [default: by the extension of the file, or json]
+        #[arg(long, value_enum)]
+        format: Option<export::SnapshotFormat>,
+        /// What to do with the partitions which already exist: fail, skip or overwrite
+        #[arg(long, default_value_t = Conflict::Fail)]
+        on_conflict: Conflict,
+    },
+
     /// Unbind ports from the partition
     Unbind {
         /// The pkey of the partition
@@ -115,21 +306,103 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum PortCommand {
+    /// Disable the port, e.g. to isolate a flapping host
+    Disable(PortArgs),
+    /// Enable the port
+    Enable(PortArgs),
+    /// Reset the port
+    Reset(PortArgs),
+}
+
+#[derive(Args)]
+struct PortArgs {
+    /// The GUID of the port, or the name of its system and its number, e.g. node1/1
+    port: PortId,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), UFMError> {
-    env_logger::init();
+    // The logs of libonm are in tracing, e.g. `RUST_LOG=libonm::rest=debug` for the spans of
+    // the requests to UFM, with the credentials redacted.
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .from_env_lossy();
+    let _ = tracing_subscriber::registry()
+        .with(fmt::Layer::default().compact().with_writer(std::io::stderr))
+        .with(env_filter)
+        .try_init();
 
     let opt: Options = Options::parse();
 
-    let conf = load_conf(&opt);
+    let conf = load_conf(&opt).await?;
+    let dry_run = opt.dry_run;
     match &opt.command {
-        Some(Commands::Delete { pkey }) => delete::run(conf, pkey).await?,
+        Some(Commands::Delete { pkey }) => delete::run(conf, pkey, dry_run).await?,
         Some(Commands::Version) => version::run(conf).await?,
-        Some(Commands::Info) => info::run(conf).await?,
-        Some(Commands::List) => list::run(conf).await?,
-        Some(Commands::View { pkey }) => view::run(conf, pkey).await?,
-        Some(Commands::Bind { pkey, guids }) => bind::run(conf, pkey, guids).await?,
-        Some(Commands::Unbind { pkey, guids }) => unbind::run(conf, pkey, guids).await?,
+        Some(Commands::Info) => info::run(conf, opt.output).await?,
+        Some(Commands::Topology { host, dot }) => {
+            topology::run(conf, host.as_deref(), *dot, opt.output).await?
+        }
+        Some(Commands::Counters { pkey, watch }) => {
+            counters::run(conf, pkey, *watch, opt.output).await?
+        }
+        Some(Commands::Events {
+            alarms,
+            severity,
+            guid,
+            since,
+            follow,
+            interval,
+        }) => {
+            let events = events::EventsOptions {
+                alarms: *alarms,
+                severity: *severity,
+                guid: *guid,
+                since: *since,
+                follow: *follow,
+                interval: *interval,
+            };
+            events::run(conf, &events, opt.output).await?
+        }
+        Some(Commands::Port { action }) => {
+            let (action, args) = match action {
+                PortCommand::Disable(args) => (PortAction::Disable, args),
+                PortCommand::Enable(args) => (PortAction::Enable, args),
+                PortCommand::Reset(args) => (PortAction::Reset, args),
+            };
+            port::run(conf, action, &args.port, args.yes).await?
+        }
+        Some(Commands::List) => list::run(conf, opt.output).await?,
+        Some(Commands::View { pkey }) => view::run(conf, pkey, opt.output).await?,
+        Some(Commands::Bind {
+            pkey,
+            index0,
+            membership,
+            guids,
+        }) => {
+            let opt = bind::BindOptions {
+                pkey: pkey.to_string(),
+                index0: *index0,
+                membership: membership.to_string(),
+                guids: guids.to_vec(),
+            };
+            bind::run(conf, &opt, dry_run).await?
+        }
+        Some(Commands::Unbind { pkey, guids }) => unbind::run(conf, pkey, guids, dry_run).await?,
+        Some(Commands::Apply { file, prune }) => apply::run(conf, file, *prune, dry_run).await?,
+        Some(Commands::Export { file, format }) => {
+            export::run(conf, file.as_deref(), *format).await?
+        }
+        Some(Commands::Import {
+            file,
+            format,
+            on_conflict,
+        }) => import::run(conf, file, *format, *on_conflict, dry_run).await?,
         Some(Commands::Update {
             pkey,
             mtu,
@@ -145,24 +418,32 @@ async fn main() -> Result<(), UFMError> {
                 ipoib: *ipoib,
                 guids: vec![],
             };
-            update::run(conf, &opt).await?
+            update::run(conf, &opt, dry_run).await?
         }
 
         Some(Commands::Create {
             pkey,
+            name,
             ipoib,
             index0,
             membership,
+            mtu,
+            service_level,
+            rate_limit,
             guids,
         }) => {
             let opt = create::CreateOptions {
                 pkey: pkey.to_string(),
+                name: name.clone().unwrap_or_default(),
                 ipoib: *ipoib,
                 index0: *index0,
                 membership: membership.to_string(),
+                mtu: *mtu,
+                service_level: *service_level,
+                rate_limit: *rate_limit,
                 guids: guids.to_vec(),
             };
-            create::run(conf, &opt).await?
+            create::run(conf, &opt, dry_run).await?
         }
         None => {}
     };
@@ -170,7 +451,19 @@ async fn main() -> Result<(), UFMError> {
     Ok(())
 }
 
-fn load_conf(opt: &Options) -> UFMConfig {
+async fn load_conf(opt: &Options) -> Result<SMConfig, UFMError> {
+    if let Backend::Opensm = opt.backend {
+        let mut conf = OpenSMConfig::default();
+        if let Some(partitions) = opt.opensm_partitions.clone() {
+            conf.partitions = partitions;
+        }
+        if let Some(cmd) = opt.opensm_reread_command.clone() {
+            conf.reread_command = Some(cmd).filter(|c| !c.is_empty());
+        }
+
+        return Ok(SMConfig::OpenSM(conf));
+    }
+
     let ufm_address = match opt.ufm_address.clone() {
         Some(s) => s,
         None => panic!("UFM_ADDRESS environment or ufm_address parameter not found"),
@@ -187,11 +480,34 @@ fn load_conf(opt: &Options) -> UFMConfig {
         None
     };
 
-    UFMConfig {
+    let mut policy = RestPolicy::default();
+    if let Some(timeout) = opt.ufm_timeout {
+        policy.timeout = timeout;
+    }
+    if let Some(timeout) = opt.ufm_connect_timeout {
+        policy.connect_timeout = timeout;
+    }
+    if let Some(retries) = opt.ufm_retries {
+        policy.retries = retries;
+    }
+    policy.insecure_http = opt.ufm_insecure_http;
+
+    let conf = UFMConfig {
         address: ufm_address,
         username: opt.ufm_username.clone(),
         password: opt.ufm_password.clone(),
         token: opt.ufm_token.clone(),
         cert,
-    }
+        credential: opt.ufm_credential.clone(),
+        policy,
+        fixture: match (opt.ufm_record.clone(), opt.ufm_replay.clone()) {
+            (Some(file), _) => Some(RestFixture::Record(file)),
+            (None, Some(file)) => Some(RestFixture::Replay(file)),
+            (None, None) => None,
+        },
+        wait: opt.ufm_wait.map(Duration::from_secs),
+    };
+    let provider = cred::build(opt.credential_provider.clone());
+
+    Ok(SMConfig::Ufm(conf.resolve(provider.as_ref()).await?))
 }
@@ -0,0 +1,142 @@
+use std::fmt::{self, Display};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use libonm::sm::UFMError;
+
+/// The format of the output of `list`, `view`, `info` and `topology`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// The tables of the main columns
+    Table,
+    /// The tables of all the columns
+    Wide,
+    /// The objects in JSON
+    Json,
+    /// The objects in YAML
+    Yaml,
+}
+
+impl Output {
+    pub fn is_wide(&self) -> bool {
+        *self == Output::Wide
+    }
+}
+
+/// An object which is printed in tables, or in its serde form for JSON and YAML.
+pub trait Render: Serialize {
+    /// The tables of the object, with the extra columns if `wide`.
+    fn table(&self, wide: bool) -> String;
+}
+
+/// Print the object in the format.
+pub fn print<T: Render>(format: Output, value: &T) -> Result<(), UFMError> {
+    match format {
+        Output::Table => print!("{}", value.table(false)),
+        Output::Wide => print!("{}", value.table(true)),
+        Output::Json => {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|e| UFMError::Unknown(e.to_string()))?;
+            println!("{}", json);
+        }
+        Output::Yaml => {
+            let yaml =
+                serde_yaml::to_string(value).map_err(|e| UFMError::Unknown(e.to_string()))?;
+            print!("{}", yaml);
+        }
+    }
+
+    Ok(())
+}
+
+/// The value of an optional field, `-` if none.
+pub fn or_none<T: Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or("-".to_string())
+}
+
+// The space between the columns.
+const COLUMN_GAP: usize = 2;
+
+/// A table whose columns are sized by the longest cells, e.g. the GUIDs and system names.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    indent: usize,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: vec![],
+            indent: 0,
+        }
+    }
+
+    /// Indent the table, e.g. in the details of an object.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate() {
+                match widths.get_mut(i) {
+                    Some(w) => *w = (*w).max(cell.chars().count()),
+                    None => widths.push(cell.chars().count()),
+                }
+            }
+        }
+
+        for row in std::iter::once(&self.headers).chain(self.rows.iter()) {
+            let mut line = " ".repeat(self.indent);
+            for (cell, width) in row.iter().zip(widths.iter()) {
+                line += &format!("{:<width$}", cell, width = width + COLUMN_GAP);
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The fields of an object, one per line and aligned by the longest name.
+#[derive(Default)]
+pub struct Details {
+    fields: Vec<(String, String)>,
+}
+
+impl Details {
+    pub fn new() -> Self {
+        Self { fields: vec![] }
+    }
+
+    pub fn add(&mut self, name: &str, value: impl Display) {
+        self.fields.push((name.to_string(), value.to_string()));
+    }
+}
+
+impl Display for Details {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .fields
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (name, value) in &self.fields {
+            writeln!(f, "{:<width$} : {}", name, value, width = width)?;
+        }
+
+        Ok(())
+    }
+}
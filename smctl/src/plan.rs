@@ -0,0 +1,32 @@
+use clap::ValueEnum;
+
+use libonm::sm::{Change, SubnetManager, UFMError};
+
+/// The format of the plan printed by `--dry-run`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DryRun {
+    /// A human-readable diff
+    Text,
+    /// The plan with the exact requests in JSON
+    Json,
+}
+
+/// Print the plan of the changes instead of applying them.
+pub async fn run(
+    sm: &dyn SubnetManager,
+    changes: Vec<Change>,
+    format: DryRun,
+) -> Result<(), UFMError> {
+    let plan = sm.plan(changes).await?;
+
+    match format {
+        DryRun::Text => print!("{}", plan),
+        DryRun::Json => {
+            let json = serde_json::to_string_pretty(&plan)
+                .map_err(|e| UFMError::Unknown(e.to_string()))?;
+            println!("{}", json);
+        }
+    }
+
+    Ok(())
+}
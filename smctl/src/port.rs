@@ -0,0 +1,37 @@
+use std::io::{self, BufRead, Write};
+
+use libonm::sm::{self, PortAction, PortId, SMConfig, UFMError};
+
+pub async fn run(
+    conf: SMConfig,
+    action: PortAction,
+    port: &PortId,
+    yes: bool,
+) -> Result<(), UFMError> {
+    let SMConfig::Ufm(conf) = conf else {
+        return Err(UFMError::InvalidConfig(
+            "the port actions are only available in UFM".to_string(),
+        ));
+    };
+
+    if !yes && !confirm(&format!("{} port {}?", action, port)) {
+        eprintln!("Aborted.");
+        return Ok(());
+    }
+
+    let ufm = sm::connect(conf)?;
+    ufm.port_action(port, action).await
+}
+
+// Ask on the terminal; it's not confirmed if there's no answer, e.g. stdin is closed.
+fn confirm(question: &str) -> bool {
+    eprint!("{} [y/N] ", question);
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use libonm::sm::{self, Hop, SMConfig, System, Topology, UFMError};
+
+use crate::output::{self, Output, Render, Table};
+
+impl Render for Topology {
+    fn table(&self, wide: bool) -> String {
+        let mut headers = vec!["Name", "GUID", "Type", "Model"];
+        if wide {
+            headers.push("IP");
+        }
+
+        let mut table = Table::new(&headers);
+        for s in &self.systems {
+            let mut row = vec![
+                s.name.clone(),
+                s.guid.to_string(),
+                s.system_type.to_string(),
+                s.model.clone(),
+            ];
+            if wide {
+                row.push(s.ip.clone());
+            }
+            table.add_row(row);
+        }
+
+        format!(
+            "{}\n{} systems, {} links\n",
+            table,
+            self.systems.len(),
+            self.links.len()
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct Uplinks {
+    system: System,
+    paths: Vec<Vec<Hop>>,
+}
+
+impl Render for Uplinks {
+    fn table(&self, wide: bool) -> String {
+        let mut out = format!("{} ({})\n", self.system.name, self.system.guid);
+
+        if !wide {
+            for path in &self.paths {
+                let hops: Vec<String> = path.iter().map(|h| h.to_string()).collect();
+                out += &format!("    {}\n", hops.join(", "));
+            }
+            return out;
+        }
+
+        let mut table = Table::new(&["Path", "From", "To", "Width", "Speed", "State"]).indent(4);
+        for (i, path) in self.paths.iter().enumerate() {
+            for hop in path {
+                table.add_row(vec![
+                    (i + 1).to_string(),
+                    hop.from.to_string(),
+                    hop.to.to_string(),
+                    hop.width.clone(),
+                    hop.speed.clone(),
+                    hop.state.clone(),
+                ]);
+            }
+        }
+
+        out + &table.to_string()
+    }
+}
+
+pub async fn run(
+    conf: SMConfig,
+    host: Option<&str>,
+    dot: bool,
+    format: Output,
+) -> Result<(), UFMError> {
+    let SMConfig::Ufm(conf) = conf else {
+        return Err(UFMError::InvalidConfig(
+            "the topology is only available in UFM".to_string(),
+        ));
+    };
+    let ufm = sm::connect(conf)?;
+    let topology = ufm.topology().await?;
+
+    if dot {
+        print!("{}", topology.to_dot());
+        return Ok(());
+    }
+
+    let Some(host) = host else {
+        return output::print(format, &topology);
+    };
+
+    let system = topology
+        .find(host)
+        .cloned()
+        .ok_or(UFMError::NotFound(host.to_string()))?;
+    let paths = topology.uplinks(system.guid);
+
+    output::print(format, &Uplinks { system, paths })
+}
@@ -1,11 +1,28 @@
-use libonm::sm::{self, PartitionKey, UFMConfig, UFMError};
+use libonm::sm::{self, Change, PartitionKey, SMConfig, UFMError};
+use libonm::Guid;
 
-pub async fn run(conf: UFMConfig, pkey: &str, guids: &[String]) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
+use crate::plan::{self, DryRun};
+
+pub async fn run(
+    conf: SMConfig,
+    pkey: &str,
+    guids: &[String],
+    dry_run: Option<DryRun>,
+) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
 
     let p = PartitionKey::try_from(pkey.to_owned())?;
+    let guids = guids
+        .iter()
+        .map(|g| g.parse())
+        .collect::<Result<Vec<Guid>, _>>()?;
+
+    if let Some(format) = dry_run {
+        let changes = vec![Change::UnbindPorts { pkey: p, guids }];
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
 
-    ufm.unbind_ports(p, guids.to_owned()).await?;
+    sm.unbind_ports(p, guids).await?;
 
     Ok(())
 }
@@ -1,29 +1,54 @@
-use libonm::sm::{self, Partition, PartitionKey, PartitionQoS, UFMConfig, UFMError};
+use libonm::sm::{
+    self, Change, IbMtu, IbRate, Partition, PartitionKey, PartitionQoS, SMConfig, UFMError,
+};
+
+use crate::plan::{self, DryRun};
 
 pub struct UpdateOptions {
     pub pkey: String,
-    pub mtu: u16,
+    pub mtu: Option<IbMtu>,
     pub ipoib: bool,
-    pub service_level: u8,
-    pub rate_limit: f64,
+    pub service_level: Option<u8>,
+    pub rate_limit: Option<IbRate>,
     pub guids: Vec<String>,
 }
 
-pub async fn run(conf: UFMConfig, opt: &UpdateOptions) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
+pub async fn run(
+    conf: SMConfig,
+    opt: &UpdateOptions,
+    dry_run: Option<DryRun>,
+) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+
+    let pkey = PartitionKey::try_from(opt.pkey.clone())?;
+
+    // The QoS which is not set is kept as the current one.
+    let mut qos = PartitionQoS::default();
+    if opt.mtu.is_none() || opt.service_level.is_none() || opt.rate_limit.is_none() {
+        qos = sm
+            .get_partition(&pkey.to_string())
+            .await?
+            .qos
+            .unwrap_or_default();
+    }
 
     let p = Partition {
         name: "".to_string(),
-        pkey: PartitionKey::try_from(opt.pkey.clone())?,
+        pkey,
         ipoib: opt.ipoib,
         qos: Some(PartitionQoS {
-            mtu_limit: opt.mtu,
-            service_level: opt.service_level,
-            rate_limit: opt.rate_limit,
+            mtu_limit: opt.mtu.unwrap_or(qos.mtu_limit),
+            service_level: opt.service_level.unwrap_or(qos.service_level),
+            rate_limit: opt.rate_limit.unwrap_or(qos.rate_limit),
         }),
     };
 
-    ufm.update_partition_qos(p).await?;
+    if let Some(format) = dry_run {
+        let changes = vec![Change::UpdateQoS { partition: p }];
+        return plan::run(sm.as_ref(), changes, format).await;
+    }
+
+    sm.update_partition_qos(p).await?;
 
     Ok(())
 }
@@ -1,8 +1,8 @@
-use libonm::sm::{self, UFMConfig, UFMError};
+use libonm::sm::{self, SMConfig, UFMError};
 
-pub async fn run(conf: UFMConfig) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
-    let v = ufm.version().await?;
+pub async fn run(conf: SMConfig) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+    let v = sm.version().await?;
 
     println!("{}", v);
 
@@ -1,52 +1,76 @@
-use libonm::sm::{self, PortType, UFMConfig, UFMError};
-
-pub async fn run(conf: UFMConfig, pkey: &str) -> Result<(), UFMError> {
-    let ufm = sm::connect(conf)?;
-    let p = ufm.get_partition(pkey).await?;
-    let ps = ufm.list_port(p.pkey).await?;
-
-    let qos = p
-        .qos
-        .ok_or(UFMError::InvalidConfig("no partition qos".to_string()))?;
-
-    println!("{:15}: {}", "Name", p.name);
-    println!("{:15}: {}", "Pkey", p.pkey.to_string());
-    println!("{:15}: {}", "IPoIB", p.ipoib);
-    println!("{:15}: {}", "MTU", qos.mtu_limit);
-    println!("{:15}: {}", "Rate Limit", qos.rate_limit);
-    println!("{:15}: {}", "Service Level", qos.service_level);
-    println!("{:15}: ", "Ports");
-
-    println!(
-        "    {:<20}{:<20}{:<10}{:<20}{:<10}{:<10}{:<20}{:<15}",
-        "GUID", "ParentGUID", "PortType", "SystemID", "LID", "LogState", "Name", "SystemName",
-    );
-    for port in ps {
-        let name = match port.name.clone() {
-            Some(n) => n,
-            None => "-".to_string(),
-        };
-        let parent_guid = match port.parent_guid.clone() {
-            Some(p) => p,
-            None => "-".to_string(),
-        };
-        let port_type = match port.port_type {
-            Some(PortType::Physical) => "pf".to_string(),
-            Some(PortType::Virtual) => "vf".to_string(),
-            None => "-".to_string(),
-        };
-        println!(
-            "    {:<20}{:<20}{:<10}{:<20}{:<10}{:<10}{:<20}{:<15}",
-            port.guid,
-            parent_guid,
-            port_type,
-            port.system_id,
-            port.lid,
-            port.logical_state,
-            name,
-            port.system_name,
-        )
+use serde::Serialize;
+
+use libonm::sm::{self, Partition, Port, PortType, SMConfig, UFMError};
+
+use crate::output::{self, or_none, Details, Output, Render, Table};
+
+#[derive(Serialize)]
+struct PartitionView {
+    #[serde(flatten)]
+    partition: Partition,
+    ports: Vec<Port>,
+}
+
+impl Render for PartitionView {
+    fn table(&self, wide: bool) -> String {
+        let p = &self.partition;
+
+        let mut details = Details::new();
+        details.add("Name", &p.name);
+        details.add("Pkey", p.pkey.to_string());
+        details.add("IPoIB", p.ipoib);
+        if let Some(qos) = &p.qos {
+            details.add("MTU", qos.mtu_limit);
+            details.add("Rate Limit", qos.rate_limit);
+            details.add("Service Level", qos.service_level);
+        }
+        details.add("Ports", "");
+
+        let mut headers = vec![
+            "GUID",
+            "ParentGUID",
+            "PortType",
+            "SystemID",
+            "LID",
+            "LogState",
+            "Name",
+            "SystemName",
+        ];
+        if wide {
+            headers.extend(["Membership", "Index0"]);
+        }
+
+        let mut table = Table::new(&headers).indent(4);
+        for port in &self.ports {
+            let port_type = match port.port_type {
+                Some(PortType::Physical) => Some("pf"),
+                Some(PortType::Virtual) => Some("vf"),
+                None => None,
+            };
+            let mut row = vec![
+                port.guid.to_string(),
+                or_none(port.parent_guid.as_ref()),
+                or_none(port_type),
+                port.system_id.clone(),
+                port.lid.to_string(),
+                port.logical_state.clone(),
+                or_none(port.name.as_ref()),
+                port.system_name.clone(),
+            ];
+            if wide {
+                row.extend([or_none(port.membership.as_ref()), or_none(port.index0)]);
+            }
+            table.add_row(row);
+        }
+
+        format!("{}{}", details, table)
     }
+}
+
+pub async fn run(conf: SMConfig, pkey: &str, format: Output) -> Result<(), UFMError> {
+    let sm = sm::build(conf)?;
+    let partition = sm.get_partition(pkey).await?;
+    let ports = sm.list_port(partition.pkey).await?;
 
-    Ok(())
+    output::print(format, &PartitionView { partition, ports })
 }
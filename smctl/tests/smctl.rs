@@ -0,0 +1,362 @@
+use std::process::{Output, Stdio};
+
+use http::Method;
+use tokio::process::Command;
+
+use libonm::sm::mock::{MockUfm, MOCK_PASSWORD, MOCK_USERNAME};
+use libonm::sm::{PartitionKey, PortCounters, System, SystemType};
+
+const GUID_1: &str = "1070fd0300176625";
+const GUID_2: &str = "0011223344560200";
+
+async fn smctl(mock: &MockUfm, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_smctl"))
+        .args(args)
+        .env("UFM_ADDRESS", mock.address())
+        .env("UFM_USERNAME", MOCK_USERNAME)
+        .env("UFM_PASSWORD", MOCK_PASSWORD)
+        .env_remove("UFM_TOKEN")
+        .env_remove("SM_BACKEND")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[tokio::test]
+async fn version() {
+    let mock = MockUfm::start().await.unwrap();
+
+    let output = smctl(&mock, &["version"]).await;
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), libonm::sm::mock::MOCK_VERSION);
+
+    mock.inject_error(Method::GET, "/app/ufm_version", 500);
+    assert!(!smctl(&mock, &["version"]).await.status.success());
+}
+
+#[tokio::test]
+async fn create_view_delete() {
+    let mock = MockUfm::start().await.unwrap();
+    let pkey = PartitionKey::try_from("0x5").unwrap();
+
+    let guid = format!("{}:limited", GUID_2);
+    let output = smctl(
+        &mock,
+        &[
+            "create", "--pkey", "0x5", "--name", "storage", "--guids", GUID_1, "--guids", &guid,
+        ],
+    )
+    .await;
+    assert!(output.status.success());
+    assert_eq!(mock.partition(pkey).unwrap().guids.len(), 2);
+
+    let output = smctl(&mock, &["list"]).await;
+    assert!(stdout(&output).contains("storage"));
+
+    let output = smctl(&mock, &["view", "--pkey", "0x5"]).await;
+    assert!(output.status.success());
+    assert!(stdout(&output).contains(GUID_1));
+    assert!(stdout(&output).contains(GUID_2));
+
+    let output = smctl(&mock, &["delete", "--pkey", "0x5"]).await;
+    assert!(output.status.success());
+    assert!(mock.partition(pkey).is_none());
+}
+
+#[tokio::test]
+async fn dry_run() {
+    let mock = MockUfm::start().await.unwrap();
+
+    let output = smctl(
+        &mock,
+        &["--dry-run=json", "bind", "--pkey", "0x5", "--guids", GUID_1],
+    )
+    .await;
+    assert!(output.status.success());
+
+    let plan: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        plan["steps"][0]["operations"][0]["path"],
+        "/resources/pkeys"
+    );
+    assert!(mock
+        .partition(PartitionKey::try_from("0x5").unwrap())
+        .is_none());
+}
+
+#[tokio::test]
+async fn structured_output() {
+    let mock = MockUfm::start().await.unwrap();
+    let long_name = "storage-of-the-training-cluster";
+    let output = smctl(
+        &mock,
+        &[
+            "create", "--pkey", "0x5", "--name", long_name, "--guids", GUID_1,
+        ],
+    )
+    .await;
+    assert!(output.status.success());
+
+    let output = smctl(&mock, &["list", "--output", "json"]).await;
+    assert!(output.status.success());
+    let parts: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let part = parts
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == long_name)
+        .unwrap();
+    assert_eq!(part["pkey"], 5);
+    assert_eq!(part["qos"]["mtu_limit"], 2);
+
+    let output = smctl(&mock, &["-o", "yaml", "view", "--pkey", "0x5"]).await;
+    assert!(output.status.success());
+    let view: serde_yaml::Value = serde_yaml::from_str(&stdout(&output)).unwrap();
+    assert_eq!(view["name"].as_str(), Some(long_name));
+    assert_eq!(view["ports"][0]["guid"].as_str(), Some(GUID_1));
+
+    // The columns are sized by the longest names.
+    let output = smctl(&mock, &["list", "-o", "wide"]).await;
+    assert!(output.status.success());
+    let table = stdout(&output);
+    let header = table.lines().next().unwrap();
+    assert!(header.ends_with("Ports"));
+    assert_eq!(header.find("Pkey"), Some(long_name.len() + 2));
+    let row = table.lines().find(|l| l.starts_with(long_name)).unwrap();
+    assert!(row.ends_with('1'));
+}
+
+#[tokio::test]
+async fn bind_guid_forms() {
+    let mock = MockUfm::start().await.unwrap();
+    let pkey = PartitionKey::try_from("0x5").unwrap();
+
+    // The GUIDs of the HCA and OpenSM are sent to UFM in its form.
+    let output = smctl(
+        &mock,
+        &[
+            "bind",
+            "--pkey",
+            "0x5",
+            "--guids",
+            "1070:FD03:0017:6625:limited",
+            "--guids",
+            "0x0011223344560200",
+        ],
+    )
+    .await;
+    assert!(output.status.success());
+    let mut guids: Vec<String> = mock
+        .partition(pkey)
+        .unwrap()
+        .guids
+        .iter()
+        .map(|p| p.guid.to_string())
+        .collect();
+    guids.sort();
+    assert_eq!(guids, vec![GUID_2, GUID_1]);
+
+    let output = smctl(&mock, &["bind", "--pkey", "0x5", "--guids", "node1"]).await;
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn bind_keeps_ipoib() {
+    let mock = MockUfm::start().await.unwrap();
+    let pkey = PartitionKey::try_from("0x5").unwrap();
+
+    let output = smctl(&mock, &["create", "--pkey", "0x5", "--guids", GUID_1]).await;
+    assert!(output.status.success());
+    assert!(mock.partition(pkey).unwrap().ipoib);
+
+    let output = smctl(&mock, &["bind", "--pkey", "0x5", "--guids", GUID_2]).await;
+    assert!(output.status.success());
+    let part = mock.partition(pkey).unwrap();
+    assert!(part.ipoib);
+    assert_eq!(part.guids.len(), 2);
+}
+
+#[tokio::test]
+async fn topology() {
+    let mock = MockUfm::start().await.unwrap();
+    let system = |guid: &str, name: &str, system_type| System {
+        guid: guid.parse().unwrap(),
+        name: name.to_string(),
+        system_type,
+        model: String::new(),
+        ip: String::new(),
+    };
+    mock.add_system(system("1070fd0300176624", "node1", SystemType::Host));
+    mock.add_system(system("b8cef60300000001", "leaf1", SystemType::Switch));
+    mock.add_link(
+        serde_json::from_value(serde_json::json!({
+            "source_guid": "1070fd0300176624",
+            "source_port": "1",
+            "destination_guid": "b8cef60300000001",
+            "destination_port": "3",
+        }))
+        .unwrap(),
+    );
+
+    let output = smctl(&mock, &["topology", "--host", "node1"]).await;
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("node1/1 -> leaf1/3"));
+
+    let output = smctl(&mock, &["topology", "--dot"]).await;
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("graph fabric {"));
+
+    let output = smctl(&mock, &["-o", "json", "topology"]).await;
+    let topology: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(topology["systems"].as_array().unwrap().len(), 2);
+
+    let output = smctl(&mock, &["topology", "--host", "node9"]).await;
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn counters() {
+    let mock = MockUfm::start().await.unwrap();
+    mock.add_port_with(GUID_1.parse().unwrap(), "node1");
+    mock.set_port_counters(PortCounters {
+        guid: GUID_1.parse().unwrap(),
+        symbol_errors: 3,
+        xmit_data: 500_000,
+        ..PortCounters::default()
+    });
+
+    let output = smctl(
+        &mock,
+        &[
+            "create", "--pkey", "0x5", "--guids", GUID_1, "--guids", GUID_2,
+        ],
+    )
+    .await;
+    assert!(output.status.success());
+
+    let output = smctl(&mock, &["counters", "--pkey", "0x5"]).await;
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.contains("SymbolErrors"));
+    assert!(out.contains("2.0 MB"));
+    // The ports without counters are skipped.
+    assert!(!out.contains(GUID_2));
+
+    let output = smctl(&mock, &["-o", "json", "counters", "--pkey", "0x5"]).await;
+    let counters: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(counters[0]["symbol_errors"], 3);
+
+    let output = smctl(&mock, &["counters", "--pkey", "0x5", "--watch", "0"]).await;
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn events() {
+    let mock = MockUfm::start().await.unwrap();
+    for (id, severity) in [(1, "Info"), (2, "Critical")] {
+        mock.add_event(
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "name": "Link is down",
+                "severity": severity,
+                "timestamp": "2024-05-10 09:30:00",
+                "type": "Port",
+                "object_name": format!("{}_1", GUID_1),
+            }))
+            .unwrap(),
+        );
+    }
+
+    let output = smctl(&mock, &["events"]).await;
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Link is down"));
+
+    let output = smctl(&mock, &["-o", "json", "events", "--severity", "critical"]).await;
+    let events: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(events.as_array().unwrap().len(), 1);
+    assert_eq!(events[0]["id"], 2);
+
+    let output = smctl(&mock, &["-o", "json", "events", "--alarms"]).await;
+    assert_eq!(stdout(&output).trim(), "[]");
+
+    for args in [
+        &["events", "--severity", "major"][..],
+        &["events", "--since", "2w"],
+        &["events", "--since", "100000000d"],
+        &["events", "--since", "999999999999999999d"],
+        &["events", "--interval", "10"],
+    ] {
+        assert!(!smctl(&mock, args).await.status.success(), "{:?}", args);
+    }
+}
+
+#[tokio::test]
+async fn port_actions() {
+    let mock = MockUfm::start().await.unwrap();
+    mock.add_port_with(GUID_1.parse().unwrap(), "node1");
+
+    // Not confirmed, as there's no answer on stdin.
+    let output = smctl(&mock, &["port", "disable", GUID_1]).await;
+    assert!(output.status.success());
+    assert!(!mock.requests().contains(&"POST /actions".to_string()));
+
+    let output = smctl(&mock, &["port", "disable", "node1/1", "--yes"]).await;
+    assert!(output.status.success());
+    assert!(mock.requests().contains(&"POST /actions".to_string()));
+
+    let output = smctl(&mock, &["port", "reset", "node1/2", "-y"]).await;
+    assert!(!output.status.success());
+    let output = smctl(&mock, &["port", "enable", "node1", "-y"]).await;
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn export_import() {
+    let file = std::env::temp_dir().join(format!("onm-snapshot-{}.toml", std::process::id()));
+    let file = file.to_str().unwrap();
+
+    let source = MockUfm::start().await.unwrap();
+    let guid = format!("{}:limited", GUID_2);
+    let output = smctl(
+        &source,
+        &[
+            "create", "--pkey", "0x5", "--name", "storage", "--guids", GUID_1, "--guids", &guid,
+        ],
+    )
+    .await;
+    assert!(output.status.success());
+
+    let output = smctl(&source, &["export", "--file", file]).await;
+    assert!(output.status.success());
+    let snapshot = std::fs::read_to_string(file).unwrap();
+    assert!(snapshot.contains("[[partition]]"));
+    assert!(snapshot.contains("limited"));
+
+    let output = smctl(&source, &["export"]).await;
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["partition"][0]["pkey"], "0x5");
+
+    let mock = MockUfm::start().await.unwrap();
+    let pkey = PartitionKey::try_from("0x5").unwrap();
+    let output = smctl(&mock, &["--dry-run", "import", "--file", file]).await;
+    assert!(output.status.success());
+    assert!(mock.partition(pkey).is_none());
+
+    let output = smctl(&mock, &["import", "--file", file]).await;
+    assert!(output.status.success());
+    assert_eq!(mock.partition(pkey).unwrap().guids.len(), 2);
+
+    // The partition exists, which fails the import by default.
+    let output = smctl(&mock, &["import", "--file", file]).await;
+    assert!(!output.status.success());
+    let output = smctl(&mock, &["import", "--file", file, "--on-conflict", "skip"]).await;
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "No changes.");
+
+    std::fs::remove_file(file).unwrap();
+}
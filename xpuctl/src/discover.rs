@@ -1,12 +1,17 @@
+use libonm::cred::CredentialProvider;
 use libonm::xpu::{XPUError, BMC, XPU};
 
 use crate::types::Context;
 
-pub async fn run(cxt: &Context) -> Result<(), XPUError> {
+pub async fn run(cxt: &Context, provider: &dyn CredentialProvider) -> Result<(), XPUError> {
     println!("{:<20}{:<30}{:<50}", "Name", "BMC", "Status");
 
     for bmc in cxt.bmc.iter() {
-        match XPU::new(&BMC::from(bmc)).await {
+        let xpu = match BMC::from(bmc).resolve(provider).await {
+            Ok(bmc) => XPU::new(&bmc).await,
+            Err(e) => Err(e),
+        };
+        match xpu {
             Ok(_) => println!("{:<20}{:<30}{:<50}", bmc.name, bmc.address, "Ok"),
             Err(e) => println!("{:<20}{:<30}{:<50}", bmc.name, bmc.address, e.to_string()),
         }
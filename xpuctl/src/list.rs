@@ -1,14 +1,16 @@
+use libonm::cred::CredentialProvider;
 use libonm::xpu::{XPUError, BMC, XPU};
 
 use crate::types::Context;
 
-pub async fn run(cxt: &Context) -> Result<(), XPUError> {
+pub async fn run(cxt: &Context, provider: &dyn CredentialProvider) -> Result<(), XPUError> {
     println!(
         "{:<20}{:<10}{:<15}{:<10}{:<15}{:<15}{}",
         "ID", "Status", "Vendor", "FW", "SN", "BMC", "Address"
     );
     for bmc in cxt.bmc.iter() {
-        let xpu = XPU::new(&BMC::from(bmc)).await?;
+        let bmc_conf = BMC::from(bmc).resolve(provider).await?;
+        let xpu = XPU::new(&bmc_conf).await?;
         println!(
             "{:<20}{:<10}{:<15}{:<10}{:<15}{:<15}{}",
             bmc.name,
@@ -6,6 +6,7 @@ use std::path::PathBuf;
 
 use tracing_subscriber::{filter::EnvFilter, filter::LevelFilter, fmt, prelude::*};
 
+use libonm::cred;
 use types::Context;
 
 mod discover;
@@ -103,18 +104,25 @@ async fn main() -> Result<(), Box<dyn Error>> {
     );
 
     for bmc in cxt.bmc.iter_mut() {
-        if bmc.password.is_none() {
-            bmc.password = Some(cxt.password.clone());
+        if bmc.password.is_none() && bmc.credential.is_none() {
+            bmc.password = cxt.password.clone();
+            bmc.credential = cxt.credential.clone();
         }
 
         if bmc.username.is_none() {
             bmc.username = Some(cxt.username.clone());
         }
+
+        if bmc.policy.is_none() {
+            bmc.policy = Some(cxt.policy.clone());
+        }
     }
 
+    let provider = cred::build(cxt.credential_provider.clone());
+
     match &args.subcommand {
-        SubCommand::Discover => discover::run(&cxt).await?,
-        SubCommand::List => list::run(&cxt).await?,
+        SubCommand::Discover => discover::run(&cxt, provider.as_ref()).await?,
+        SubCommand::List => list::run(&cxt, provider.as_ref()).await?,
         SubCommand::View { xpu } => view::run(&cxt, *xpu).await?,
     }
 
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use libonm::cred::CredentialConfig;
+use libonm::{RestFixture, RestPolicy};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BMC {
     pub name: String,
@@ -7,12 +10,31 @@ pub struct BMC {
     pub address: String,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// The name of the credential of the password in the credential provider; the password
+    /// and credential of Context are used if neither of them is set.
+    #[serde(default)]
+    pub credential: Option<String>,
+    /// The timeouts and retries of the requests to BMC, the one of Context if None.
+    #[serde(default)]
+    pub policy: Option<RestPolicy>,
+    /// Record the requests to BMC into a fixture file, or replay them from it, e.g.
+    /// `fixture = { record = "bf2.json" }`.
+    #[serde(default)]
+    pub fixture: Option<RestFixture>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Context {
     pub username: String,
-    pub password: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub credential: Option<String>,
+    /// The provider of the credentials, the environment variables by default.
+    #[serde(default)]
+    pub credential_provider: CredentialConfig,
+    #[serde(default)]
+    pub policy: RestPolicy,
 
     pub bmc: Vec<BMC>,
 }
@@ -22,7 +44,10 @@ impl From<&BMC> for libonm::xpu::BMC {
         libonm::xpu::BMC {
             username: bmc.username.clone().unwrap(),
             address: bmc.address.clone(),
-            password: bmc.password.clone().unwrap(),
+            password: bmc.password.clone().unwrap_or_default(),
+            credential: bmc.credential.clone(),
+            policy: bmc.policy.clone().unwrap_or_default(),
+            fixture: bmc.fixture.clone(),
         }
     }
 }